@@ -0,0 +1,236 @@
+use super::*;
+
+// Players fishing closer than this to each other are fishing together
+const FISHING_TOGETHER_DISTANCE: f32 = 5.0;
+const TOAST_TIME: f32 = 4.0;
+// Points of a spawn region looked at to tell how deep it is
+const REGION_SAMPLES: usize = 32;
+
+// Part of the world a species lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Region {
+    Shallows,
+    DeepSea,
+    Land,
+    Space,
+}
+
+impl Region {
+    // Looked up through World::regions, this is only worked out once per world
+    pub fn classify(map: &Map, spawn_circle: &SpawnCircle) -> Self {
+        match spawn_circle.behavior {
+            FishBehavior::Space => Self::Space,
+            FishBehavior::Land => Self::Land,
+            _ if Self::lives_deep(map, spawn_circle) => Self::DeepSea,
+            _ => Self::Shallows,
+        }
+    }
+
+    fn lives_deep(map: &Map, spawn_circle: &SpawnCircle) -> bool {
+        let is_deep = |pos| map.get_height(pos) < DEEP_SEA_HEIGHT;
+        let Some(region) = &spawn_circle.region else { return is_deep(spawn_circle.center) };
+        // Same seed everywhere, so that clients and the server agree
+        let mut rng = StdRng::seed_from_u64(0);
+        let deep = (0..REGION_SAMPLES)
+            .filter_map(|_| region.sample(map, &mut rng))
+            .filter(|&pos| is_deep(pos))
+            .count();
+        deep * 2 > REGION_SAMPLES
+    }
+
+    pub fn of(world: &World, fish_type: FishType) -> Self {
+        world.regions[fish_type]
+    }
+
+    pub fn species(self, world: &World) -> impl Iterator<Item = FishType> + '_ {
+        (0..world.regions.len()).filter(move |&fish_type| world.regions[fish_type] == self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstCatch,
+    FirstBoat,
+    // Caught every species of the region
+    Region(Region),
+    // Flew the airship over the edge of the world
+    EdgeOfTheWorld,
+    FishingTogether,
+}
+
+impl Achievement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FirstCatch => "First catch",
+            Self::FirstBoat => "Ahoy",
+            Self::Region(Region::Shallows) => "Master of the shallows",
+            Self::Region(Region::DeepSea) => "Master of the deep sea",
+            Self::Region(Region::Land) => "Master of the land",
+            Self::Region(Region::Space) => "Master of space",
+            Self::EdgeOfTheWorld => "Over the edge",
+            Self::FishingTogether => "Fishing buddies",
+        }
+    }
+}
+
+// A message that pops up for a few seconds
+pub struct Toast {
+    pub text: String,
+    pub lifetime: f32,
+}
+
+impl Model {
+    fn unlock(&mut self, player_id: Id, achievement: Achievement, events: &mut Vec<Event>) {
+        let Some(player) = self.players.get_mut(&player_id) else { return };
+        if player.achievements.insert(achievement) {
+            events.push(Event::AchievementUnlocked {
+                player: player_id,
+                achievement,
+            });
+        }
+    }
+
+    // Hands out the achievements earned by the events since `first_event`
+    pub fn award_achievements(&mut self, first_event: usize, events: &mut Vec<Event>) {
+        let mut unlocked = Vec::new();
+        for event in &events[first_event..] {
+            match *event {
+                Event::CaughtFish { player, caught, .. } => {
+                    unlocked.push((player, Achievement::FirstCatch));
+                    let region = Region::of(&self.world, caught.fish_type);
+                    let Some(catcher) = self.players.get(&player) else { continue };
+                    if region
+                        .species(&self.world)
+                        .all(|fish_type| catcher.fishdex.contains(&fish_type))
+                    {
+                        unlocked.push((player, Achievement::Region(region)));
+                    }
+                }
+                Event::BoughtBoat { player, .. } => {
+                    unlocked.push((player, Achievement::FirstBoat));
+                }
+                _ => {}
+            }
+        }
+        for (player, achievement) in unlocked {
+            self.unlock(player, achievement, events);
+        }
+    }
+
+    // Achievements for where the players are rather than what they did
+    pub fn update_achievements(&mut self, events: &mut Vec<Event>) {
+        let mut players: Vec<&Player> = self.players.iter().collect();
+        players.sort_by_key(|player| player.id.0);
+        let mut unlocked = Vec::new();
+        for player in &players {
+            if player.boat_level >= 3 && self.world.map.get_is_void(player.pos.pos) {
+                unlocked.push((player.id, Achievement::EdgeOfTheWorld));
+            }
+            if player.fishing_state.bobber_pos().is_none() {
+                continue;
+            }
+            let buddy = players.iter().any(|other| {
+                other.id != player.id
+                    && other.fishing_state.bobber_pos().is_some()
+                    && (other.pos.pos - player.pos.pos).len() < FISHING_TOGETHER_DISTANCE
+            });
+            if buddy {
+                unlocked.push((player.id, Achievement::FishingTogether));
+            }
+        }
+        for (player, achievement) in unlocked {
+            self.unlock(player, achievement, events);
+        }
+    }
+}
+
+impl Game {
+    pub fn show_achievement(&mut self, player_id: Id, achievement: Achievement) {
+        let text = if player_id == self.player_id {
+            self.play_sound(self.player.pos.pos, SoundType::Ding);
+            format!("achievement unlocked: {}", achievement.name())
+        } else {
+            let model = self.model.get();
+            let name = model
+                .players
+                .get(&player_id)
+                .map_or("someone", |player| player.name.as_str());
+            format!("{name} unlocked {}", achievement.name())
+        };
+        self.toasts.push(Toast {
+            text,
+            lifetime: 0.0,
+        });
+    }
+
+    pub fn update_toasts(&mut self, delta_time: f32) {
+        for toast in &mut self.toasts {
+            toast.lifetime += delta_time;
+        }
+        self.toasts.retain(|toast| toast.lifetime < TOAST_TIME);
+    }
+
+    pub fn draw_toasts(&self, framebuffer: &mut ugli::Framebuffer) {
+        let camera = geng::Camera2d {
+            center: Vec2::ZERO,
+            rotation: 0.0,
+            fov: 15.0,
+        };
+        let text: Vec<&str> = self
+            .toasts
+            .iter()
+            .map(|toast| toast.text.as_str())
+            .collect();
+        self.draw_text(
+            framebuffer,
+            &camera,
+            &text.join("\n"),
+            vec2(0.0, camera.fov / 2.0 - 3.0),
+        );
+    }
+}
+
+#[test]
+fn test_first_catch() {
+    let mut model = Model::seeded(WorldRef::default_world(), 5);
+    let mut events = Vec::new();
+    let first = model.id_gen.gen();
+    let second = model.id_gen.gen();
+    model.players.insert(Player::new(first, Vec2::ZERO));
+    model.players.insert(Player::new(second, Vec2::ZERO));
+    let caught = CaughtFishInstance::average(0, &model.world.fish_configs.configs[0]);
+    model.give_fish(first, caught, &mut events);
+    events.push(Event::CaughtFish {
+        player: first,
+        fish: Id(0),
+        caught,
+        position: Vec2::ZERO,
+    });
+    model.award_achievements(0, &mut events);
+    let unlocked = |player: Id| model.players.get(&player).unwrap().achievements.clone();
+    assert!(unlocked(first).contains(&Achievement::FirstCatch));
+    assert!(!unlocked(second).contains(&Achievement::FirstCatch));
+
+    // Only announced once
+    let announced = events.len();
+    model.award_achievements(0, &mut events);
+    assert_eq!(events.len(), announced);
+}
+
+#[test]
+fn test_fishing_together() {
+    let mut model = Model::seeded(WorldRef::default_world(), 5);
+    let mut events = Vec::new();
+    let first = model.id_gen.gen();
+    let second = model.id_gen.gen();
+    let far = model.id_gen.gen();
+    for (player, x) in [(first, 10.0), (second, 12.0), (far, 40.0)] {
+        let mut player = Player::new(player, vec2(x, 0.0));
+        player.fishing_state = FishingState::Waiting(Vec2::ZERO);
+        model.players.insert(player);
+    }
+    model.update_achievements(&mut events);
+    let unlocked = |player: Id| model.players.get(&player).unwrap().achievements.clone();
+    assert!(unlocked(second).contains(&Achievement::FishingTogether));
+    assert!(!unlocked(far).contains(&Achievement::FishingTogether));
+}
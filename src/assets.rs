@@ -12,20 +12,64 @@ pub struct Shaders {
     pub wave: ugli::Program,
 }
 
-#[derive(geng::Assets, Serialize, Deserialize)]
-#[asset(json)]
+#[derive(Serialize, Deserialize)]
 pub struct Config {
     pub inventory_size: usize,
     pub space_color: Rgba<f32>,
+    #[serde(default)]
+    pub day: DayConfig,
+    #[serde(default)]
+    pub weather: WeatherConfig,
+    #[serde(default)]
+    pub quests: QuestConfig,
+    #[serde(default)]
+    pub stocks: StockConfig,
+    // Filled in from the map manifest
+    #[serde(default)]
     pub fish_shops: Vec<Vec2<f32>>,
+    #[serde(default)]
+    pub baits: Vec<BaitConfig>,
+    // The first rod is the one everyone starts with
+    pub rod_types: Vec<RodConfig>,
     pub boat_types: Vec<BoatConfig>,
 }
 
+// Textures in static/assets/rods, one for every rod type but the first
+pub const ROD_TEXTURES: usize = 3;
+
+impl Config {
+    pub fn rod(&self, level: u8) -> &RodConfig {
+        &self.rod_types[(level as usize).min(self.rod_types.len() - 1)]
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RodConfig {
+    pub name: String,
+    pub cost: u32,
+    // How far the bobber can be cast and how far away it can get before the line snaps
+    pub line_length: f32,
+    // Fish this close to the bobber swim up to it
+    pub attract_distance: f32,
+    // Multiplies the reeling speed and divides the tension it builds up
+    pub reel_strength: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BaitConfig {
+    pub name: String,
+    pub cost: u32,
+    // How many casts one purchase is good for
+    pub amount: u32,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct BoatConfig {
     pub name: String,
     pub cost: u32,
     pub scale: f32,
+    // Filled in from the map manifest
+    #[serde(default)]
     pub shops: Vec<Vec2<f32>>,
 }
 
@@ -127,8 +171,10 @@ pub struct Assets {
     )]
     pub ships: Vec<ShipAssets>,
     pub bobber: ugli::Texture,
+    // Upgraded rods, the starting one is fishing_rod, the range has to match ROD_TEXTURES
+    #[asset(range = "1..=3", path = "rods/*.png")]
+    pub rods: Vec<ugli::Texture>,
     pub player: PlayerAssets,
-    pub config: Config,
     #[asset(path = "PerlinNoise.png", postprocess = "make_repeated")]
     pub surface_noise: ugli::Texture,
     #[asset(path = "WaterDistortion.png", postprocess = "make_repeated")]
@@ -136,8 +182,6 @@ pub struct Assets {
     #[asset(load_with = "load_fishes(&geng, &base_path.join(\"fish\"))")]
     pub fishes: Vec<FishAssets>,
     pub fishing_rod: ugli::Texture,
-    pub map: ugli::Texture,
-    pub map_color: ugli::Texture,
     #[asset(path = "music.mp3", postprocess = "make_looped")]
     pub music: geng::Sound,
     #[asset(path = "space_music.mp3", postprocess = "make_looped")]
@@ -166,6 +210,8 @@ pub struct SpawnCircle {
     pub inner_radius: Option<f32>,
     pub behavior: FishBehavior,
     pub reversed: Option<bool>,
+    // Spawn area used instead of the circle, the circle still drives the behavior
+    pub region: Option<SpawnRegion>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -175,6 +221,14 @@ pub struct FishConfig {
     pub spawn_circle: SpawnCircle,
     pub count: u32,
     pub size: f32,
+    #[serde(default)]
+    pub catch: CatchConfig,
+    #[serde(default)]
+    pub struggle: StruggleConfig,
+    #[serde(default)]
+    pub bite: BiteConfig,
+    #[serde(default)]
+    pub respawn: RespawnConfig,
 }
 
 pub struct FishAssets {
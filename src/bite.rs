@@ -0,0 +1,230 @@
+use super::*;
+
+// How eagerly a species bites, configured per species in `list.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BiteConfig {
+    // Expected bites per second of a fish next to a freshly cast bobber
+    pub rate: f32,
+    // How much the rate grows per second the bobber has been waiting
+    pub patience: f32,
+    // Cap on the growth from waiting, relative to the base rate
+    pub max_patience_bonus: f32,
+    // Bite rate multipliers by bait name, baits that are not listed do not change the rate
+    pub baits: HashMap<String, f32>,
+    // Only comes to the bobber when one of the listed baits is used
+    pub bait_only: bool,
+    // Hours of the day when the species is about, can wrap over midnight
+    pub active_hours: Option<[f32; 2]>,
+}
+
+impl Default for BiteConfig {
+    fn default() -> Self {
+        Self {
+            rate: 0.05,
+            patience: 0.02,
+            max_patience_bonus: 1.0,
+            baits: HashMap::new(),
+            bait_only: false,
+            active_hours: None,
+        }
+    }
+}
+
+// Everything around the bobber that changes the bite rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiteModifiers {
+    // Seconds since the bobber landed
+    pub waited: f32,
+    pub bait: Option<String>,
+    pub hour: f32,
+    pub weather: Weather,
+    // What is left of the fish around the bobber
+    pub stock: f32,
+}
+
+impl BiteConfig {
+    fn bait_multiplier(&self, modifiers: &BiteModifiers) -> Option<f32> {
+        let multiplier = modifiers
+            .bait
+            .as_ref()
+            .and_then(|bait| self.baits.get(bait).copied());
+        if self.bait_only {
+            multiplier
+        } else {
+            Some(multiplier.unwrap_or(1.0))
+        }
+    }
+
+    // Whether the fish swims up to the bobber at all
+    pub fn attracted(&self, modifiers: &BiteModifiers) -> bool {
+        let active = self
+            .active_hours
+            .map_or(true, |hours| TimeOfDay(modifiers.hour).within(hours));
+        let baited = self
+            .bait_multiplier(modifiers)
+            .map_or(false, |multiplier| multiplier > 0.0);
+        active && baited
+    }
+
+    pub fn rate(&self, modifiers: &BiteModifiers) -> f32 {
+        let patience = (modifiers.waited * self.patience).min(self.max_patience_bonus);
+        self.rate
+            * (1.0 + patience)
+            * self.bait_multiplier(modifiers).unwrap_or(0.0)
+            * modifiers.weather.bite_multiplier()
+            * modifiers.stock
+    }
+
+    // Chance to bite within one step, independent of the tick rate
+    pub fn chance(&self, modifiers: &BiteModifiers, delta_time: f32) -> f64 {
+        let rate = self.rate(modifiers).max(0.0);
+        (1.0 - (-rate * delta_time).exp()) as f64
+    }
+}
+
+impl Model {
+    // Remembers when every bobber landed, so that fish bite more the longer players wait
+    pub fn update_bobber_times(&mut self) {
+        let time = self.time;
+        let waiting: HashSet<Id> = self
+            .players
+            .iter()
+            .filter(|player| matches!(player.fishing_state, FishingState::Waiting(_)))
+            .map(|player| player.id)
+            .collect();
+        self.bobber_times.retain(|id, _| waiting.contains(id));
+        for id in waiting {
+            self.bobber_times.entry(id).or_insert(time);
+        }
+    }
+
+    pub fn bite_modifiers(&self, player_id: Id) -> BiteModifiers {
+        let landed = self.bobber_times.get(&player_id).copied();
+        let player = self.players.get(&player_id);
+        let bait = player
+            .and_then(|player| player.active_bait())
+            .and_then(|bait| self.world.config.baits.get(bait));
+        let bobber_pos = player.and_then(|player| player.fishing_state.bobber_pos());
+        BiteModifiers {
+            waited: self.time - landed.unwrap_or(self.time),
+            bait: bait.map(|bait| bait.name.clone()),
+            hour: self.time_of_day().0,
+            weather: self.weather,
+            stock: bobber_pos.map_or(1.0, |pos| self.stock(pos)),
+        }
+    }
+}
+
+#[test]
+fn test_bite_chance() {
+    let config = BiteConfig {
+        rate: 0.1,
+        patience: 0.1,
+        max_patience_bonus: 1.0,
+        ..default()
+    };
+    let mut rng = StdRng::seed_from_u64(1234);
+    let delta_time = 0.1;
+    const TICKS: usize = 100_000;
+    for (waited, expected_rate) in [(0.0, 0.1), (5.0, 0.15), (100.0, 0.2)] {
+        let modifiers = BiteModifiers {
+            waited,
+            bait: None,
+            hour: 12.0,
+            weather: Weather::Clear,
+            stock: 1.0,
+        };
+        let chance = config.chance(&modifiers, delta_time);
+        let bites = (0..TICKS).filter(|_| rng.gen_bool(chance)).count();
+        let expected = TICKS as f32 * (1.0 - (-expected_rate * delta_time).exp());
+        // Well within 5 standard deviations
+        assert!((bites as f32 - expected).abs() < 5.0 * expected.sqrt());
+    }
+}
+
+#[test]
+fn test_bait() {
+    let config: BiteConfig =
+        serde_json::from_str(r#"{ "rate": 0.1, "baits": { "worms": 2, "salt": 0 } }"#).unwrap();
+    let picky: BiteConfig =
+        serde_json::from_str(r#"{ "baits": { "golden lure": 3 }, "bait_only": true }"#).unwrap();
+    let with = |bait: Option<&str>| BiteModifiers {
+        waited: 0.0,
+        bait: bait.map(|bait| bait.to_owned()),
+        hour: 12.0,
+        weather: Weather::Clear,
+        stock: 1.0,
+    };
+    assert_eq!(config.rate(&with(Some("worms"))), 0.2);
+    assert_eq!(config.rate(&with(Some("golden lure"))), 0.1);
+    let rain = BiteModifiers {
+        weather: Weather::Rain,
+        ..with(Some("worms"))
+    };
+    assert_eq!(config.rate(&rain), 0.3);
+    assert!(!config.attracted(&with(Some("salt"))));
+    assert!(!picky.attracted(&with(None)));
+    assert!(!picky.attracted(&with(Some("worms"))));
+    assert!(picky.attracted(&with(Some("golden lure"))));
+}
+
+#[test]
+fn test_active_hours() {
+    let owl: BiteConfig = serde_json::from_str(r#"{ "active_hours": [22, 4] }"#).unwrap();
+    let at = |hour| BiteModifiers {
+        waited: 0.0,
+        bait: None,
+        hour,
+        weather: Weather::Clear,
+        stock: 1.0,
+    };
+    assert!(owl.attracted(&at(23.0)));
+    assert!(owl.attracted(&at(2.0)));
+    assert!(!owl.attracted(&at(12.0)));
+    assert!(BiteConfig::default().attracted(&at(12.0)));
+}
+
+#[test]
+fn test_bites_are_reproducible() {
+    let run = || {
+        let mut model = Model::seeded(WorldRef::default_world(), 42);
+        let mut player = Player::new(model.id_gen.gen(), Vec2::ZERO);
+        let fish = model
+            .fishes
+            .iter()
+            .filter(|fish| {
+                let behavior = &model.world.fish_configs.configs[fish.index]
+                    .spawn_circle
+                    .behavior;
+                *behavior != FishBehavior::Land
+            })
+            .min_by_key(|fish| fish.id.0)
+            .unwrap()
+            .pos
+            .pos;
+        player.pos.pos = fish + vec2(2.0, 0.0);
+        player.fishing_state = FishingState::Waiting(fish);
+        let player_id = player.id;
+        model.players.insert(player);
+        let mut bite_tick = None;
+        for tick in 0..10_000 {
+            <Model as simple_net::Model>::tick(&mut model, &mut Vec::new());
+            if model
+                .players
+                .get(&player_id)
+                .unwrap()
+                .fishing_state
+                .hooked_fish()
+                .is_some()
+            {
+                bite_tick = Some(tick);
+                break;
+            }
+        }
+        bite_tick
+    };
+    let bite_tick = run();
+    assert!(bite_tick.is_some());
+    assert_eq!(bite_tick, run());
+}
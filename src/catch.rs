@@ -0,0 +1,170 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    pub const ALL: [Self; 5] = [
+        Self::Common,
+        Self::Uncommon,
+        Self::Rare,
+        Self::Epic,
+        Self::Legendary,
+    ];
+
+    pub fn default_chances() -> HashMap<Self, f32> {
+        HashMap::from_iter([
+            (Self::Common, 60.0),
+            (Self::Uncommon, 25.0),
+            (Self::Rare, 10.0),
+            (Self::Epic, 4.0),
+            (Self::Legendary, 1.0),
+        ])
+    }
+
+    pub fn price_multiplier(&self) -> f32 {
+        match self {
+            Self::Common => 1.0,
+            Self::Uncommon => 1.5,
+            Self::Rare => 2.5,
+            Self::Epic => 5.0,
+            Self::Legendary => 10.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Common => "common",
+            Self::Uncommon => "uncommon",
+            Self::Rare => "rare",
+            Self::Epic => "epic",
+            Self::Legendary => "legendary",
+        }
+    }
+}
+
+// What a caught fish of this species can turn out to be
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatchConfig {
+    // Kilograms
+    pub min_weight: f32,
+    pub max_weight: f32,
+    // Centimeters
+    pub min_length: f32,
+    pub max_length: f32,
+    // Relative chances of the rarity tiers, missing tiers never roll
+    #[serde(default = "Rarity::default_chances")]
+    pub rarity: HashMap<Rarity, f32>,
+}
+
+impl Default for CatchConfig {
+    fn default() -> Self {
+        Self {
+            min_weight: 0.5,
+            max_weight: 2.0,
+            min_length: 20.0,
+            max_length: 45.0,
+            rarity: Rarity::default_chances(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CaughtFishInstance {
+    pub fish_type: FishType,
+    pub rarity: Rarity,
+    pub weight: f32,
+    pub length: f32,
+}
+
+impl CaughtFishInstance {
+    pub fn roll(fish_type: FishType, config: &FishConfig, rng: &mut impl Rng) -> Self {
+        let rarity = *Rarity::ALL
+            .choose_weighted(rng, |rarity| {
+                config.catch.rarity.get(rarity).copied().unwrap_or(0.0)
+            })
+            .unwrap_or(&Rarity::Common);
+        // Average of two rolls so that the extremes are rare
+        let t = (rng.gen_range(0.0..=1.0) + rng.gen_range(0.0..=1.0)) / 2.0;
+        Self::with_size(fish_type, config, rarity, t)
+    }
+
+    // The size is between 0 for the smallest and 1 for the biggest fish of the species
+    pub fn with_size(fish_type: FishType, config: &FishConfig, rarity: Rarity, t: f32) -> Self {
+        let catch = &config.catch;
+        Self {
+            fish_type,
+            rarity,
+            weight: catch.min_weight + (catch.max_weight - catch.min_weight) * t,
+            length: catch.min_length + (catch.max_length - catch.min_length) * t,
+        }
+    }
+
+    // A common fish of middling size
+    pub fn average(fish_type: FishType, config: &FishConfig) -> Self {
+        Self::with_size(fish_type, config, Rarity::Common, 0.5)
+    }
+
+    // Bigger fish sell for up to 1.5 times the base price, smaller ones for down to half of it
+    pub fn price(&self, config: &FishConfig) -> u32 {
+        let catch = &config.catch;
+        let t = if catch.max_weight > catch.min_weight {
+            ((self.weight - catch.min_weight) / (catch.max_weight - catch.min_weight))
+                .clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        let price = config.cost as f32 * (0.5 + t) * self.rarity.price_multiplier();
+        (price.round() as u32).max(1)
+    }
+
+    pub fn description(&self, config: &FishConfig) -> String {
+        format!(
+            "{} {} ({:.1}kg, {:.0}cm)",
+            self.rarity.name(),
+            config.name,
+            self.weight,
+            self.length,
+        )
+    }
+}
+
+#[test]
+fn test_catch_rolls() {
+    let config: FishConfig = serde_json::from_str(
+        r#"{
+            "name": "Test fish",
+            "cost": 10,
+            "size": 1,
+            "spawn_circle": { "center": [0, 0], "radius": 5, "behavior": "Chaos" },
+            "count": 1,
+            "catch": {
+                "min_weight": 1,
+                "max_weight": 3,
+                "min_length": 10,
+                "max_length": 30,
+                "rarity": { "Epic": 1 }
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut rng = StdRng::seed_from_u64(1234);
+    for _ in 0..100 {
+        let fish = CaughtFishInstance::roll(0, &config, &mut rng);
+        assert_eq!(fish.rarity, Rarity::Epic);
+        assert!((1.0..=3.0).contains(&fish.weight));
+        assert!((10.0..=30.0).contains(&fish.length));
+    }
+    let smallest = CaughtFishInstance::with_size(0, &config, Rarity::Common, 0.0);
+    let biggest = CaughtFishInstance::with_size(0, &config, Rarity::Common, 1.0);
+    let legendary = CaughtFishInstance::with_size(0, &config, Rarity::Legendary, 1.0);
+    assert_eq!(smallest.price(&config), 5);
+    assert_eq!(biggest.price(&config), 15);
+    assert_eq!(legendary.price(&config), 150);
+}
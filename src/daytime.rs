@@ -0,0 +1,122 @@
+use super::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayConfig {
+    // Real seconds in one in-game day
+    pub length: f32,
+    // Light at midnight, the noon is fully lit
+    pub night_light: Rgba<f32>,
+    pub dusk_light: Rgba<f32>,
+}
+
+impl Default for DayConfig {
+    fn default() -> Self {
+        Self {
+            length: 600.0,
+            night_light: Rgba::new(0.35, 0.4, 0.65, 1.0),
+            dusk_light: Rgba::new(1.0, 0.75, 0.6, 1.0),
+        }
+    }
+}
+
+fn mix(a: Rgba<f32>, b: Rgba<f32>, t: f32) -> Rgba<f32> {
+    Rgba::new(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    )
+}
+
+// Hours since midnight, from 0 to 24
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct TimeOfDay(pub f32);
+
+impl TimeOfDay {
+    // The world starts in the morning
+    const START_HOUR: f32 = 8.0;
+
+    pub fn new(time: f32, config: &DayConfig) -> Self {
+        let hours = Self::START_HOUR + time / config.length * 24.0;
+        Self(hours.rem_euclid(24.0))
+    }
+
+    // Whether the time is within the window, which can wrap over midnight
+    pub fn within(&self, [from, to]: [f32; 2]) -> bool {
+        if from <= to {
+            (from..to).contains(&self.0)
+        } else {
+            self.0 >= from || self.0 < to
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.within([20.0, 6.0])
+    }
+
+    // Goes from 0 at midnight to 1 at noon
+    pub fn daylight(&self) -> f32 {
+        (1.0 - (self.0 / 24.0 * 2.0 * f32::PI).cos()) / 2.0
+    }
+
+    pub fn light(&self, config: &DayConfig) -> Rgba<f32> {
+        let daylight = self.daylight();
+        // Warmer light around sunrise and sunset
+        let dusk = 1.0 - (daylight - 0.5).abs() * 2.0;
+        let light = mix(config.night_light, Rgba::WHITE, daylight);
+        mix(light, config.dusk_light, dusk * 0.5)
+    }
+}
+
+impl std::fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let minutes = (self.0 * 60.0) as u32;
+        write!(f, "{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+impl Model {
+    pub fn time_of_day(&self) -> TimeOfDay {
+        TimeOfDay::new(self.time, &self.world.config.day)
+    }
+}
+
+impl Game {
+    // Color of the sunlight, multiplied into the world shaders
+    pub fn light(&self) -> Rgba<f32> {
+        self.model.get().time_of_day().light(&self.world.config.day)
+    }
+
+    pub fn draw_clock(&self, framebuffer: &mut ugli::Framebuffer, camera: &geng::Camera2d) {
+        let time_of_day = self.model.get().time_of_day();
+        let text = if time_of_day.is_night() {
+            format!("{time_of_day} night")
+        } else {
+            time_of_day.to_string()
+        };
+        self.draw_text(
+            framebuffer,
+            camera,
+            &text,
+            vec2(-5.0, camera.fov / 2.0 - 1.0),
+        );
+    }
+}
+
+#[test]
+fn test_time_of_day() {
+    let config = DayConfig {
+        length: 240.0,
+        ..default()
+    };
+    assert_eq!(TimeOfDay::new(0.0, &config).to_string(), "08:00");
+    assert_eq!(TimeOfDay::new(60.0, &config).to_string(), "14:00");
+    assert_eq!(TimeOfDay::new(180.0, &config).to_string(), "02:00");
+    assert!(TimeOfDay::new(180.0, &config).is_night());
+    assert!(TimeOfDay(23.0).within([22.0, 4.0]));
+    assert!(!TimeOfDay(12.0).within([22.0, 4.0]));
+    assert!(TimeOfDay(12.0).within([6.0, 18.0]));
+    assert!(TimeOfDay(12.0).daylight() > 0.99);
+    assert!(TimeOfDay(0.0).daylight() < 0.01);
+}
@@ -0,0 +1,325 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionError {
+    UnknownPlayer,
+    InvalidSlot,
+    InvalidBoat,
+    InvalidBait,
+    InvalidRod,
+    TooFarFromShop,
+    NotEnoughMoney,
+    AlreadyOwned,
+    InvalidBounty,
+    AlreadyTaken,
+    TooManyQuests,
+    QuestNotComplete,
+}
+
+impl TransactionError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::UnknownPlayer => "you are not in the game anymore",
+            Self::InvalidSlot => "that fish is not in your inventory anymore",
+            Self::InvalidBoat | Self::InvalidBait | Self::InvalidRod => {
+                "this shop does not sell that"
+            }
+            Self::TooFarFromShop => "you need to get closer to the shop",
+            Self::NotEnoughMoney => "you don't have enough money",
+            Self::AlreadyOwned => "you already have a better one",
+            Self::InvalidBounty => "that bounty is not posted anymore",
+            Self::AlreadyTaken => "you already took that bounty",
+            Self::TooManyQuests => "you already have too many bounties",
+            Self::QuestNotComplete => "you have not finished that bounty yet",
+        }
+    }
+}
+
+// Released fish land at most this far from the player
+const MAX_RELEASE_DISTANCE: f32 = 2.0;
+
+fn distance_to_nearest(points: &[Vec2<f32>], pos: Vec2<f32>) -> f32 {
+    points
+        .iter()
+        .map(|&point| r32((point - pos).len()))
+        .min()
+        .map_or(f32::INFINITY, |distance| distance.raw())
+}
+
+impl Model {
+    // If the inventory overflows, the oldest fish is released back into the water
+    pub fn give_fish(
+        &mut self,
+        player_id: Id,
+        fish: CaughtFishInstance,
+        events: &mut Vec<Event>,
+    ) -> Option<CaughtFishInstance> {
+        let player = self.players.get_mut(&player_id)?;
+        player.fishdex.insert(fish.fish_type);
+        player.inventory.push(fish);
+        if player.inventory.len() <= self.world.config.inventory_size {
+            return None;
+        }
+        let dropped = player.inventory.remove(0);
+        let pos = player.pos.pos;
+        if self.has_room_for(dropped.fish_type) {
+            self.fishes
+                .insert(Fish::new(self.id_gen.gen(), dropped.fish_type, pos));
+        }
+        self.play_drop_sound(None, pos, events);
+        Some(dropped)
+    }
+
+    fn play_drop_sound(&self, player: Option<Id>, pos: Vec2<f32>, events: &mut Vec<Event>) {
+        events.push(Event::Sound {
+            player,
+            sound_type: if self.world.map.get_height(pos) > 0.0 {
+                SoundType::DropFishLand
+            } else {
+                SoundType::DropFishWater
+            },
+            pos,
+        });
+    }
+
+    // Lets the fish of the slot back into the water next to the player
+    pub fn release_fish(
+        &mut self,
+        player_id: Id,
+        slot: usize,
+        pos: Vec2<f32>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        if slot >= player.inventory.len() {
+            return Err(TransactionError::InvalidSlot);
+        }
+        let fish_type = player.inventory.remove(slot).fish_type;
+        let pos = player.pos.pos + (pos - player.pos.pos).clamp_len(..=MAX_RELEASE_DISTANCE);
+        // The fish swims off if there are too many of its kind already
+        if self.has_room_for(fish_type) {
+            self.fishes
+                .insert(Fish::new(self.id_gen.gen(), fish_type, pos));
+        }
+        self.play_drop_sound(Some(player_id), pos, events);
+        Ok(())
+    }
+
+    pub fn sell_fish(
+        &mut self,
+        player_id: Id,
+        slot: usize,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        if slot >= player.inventory.len() {
+            return Err(TransactionError::InvalidSlot);
+        }
+        let shop_distance = distance_to_nearest(&self.world.config.fish_shops, player.pos.pos);
+        if shop_distance > SHOPPING_DISTANCE {
+            return Err(TransactionError::TooFarFromShop);
+        }
+        let fish = player.inventory.remove(slot);
+        let price = fish.price(&self.world.fish_configs.configs[fish.fish_type]);
+        player.money += price;
+        events.push(Event::SoldFish {
+            player: player_id,
+            fish,
+            price,
+        });
+        events.push(Event::Sound {
+            player: None,
+            sound_type: SoundType::SellFish,
+            pos: player.pos.pos,
+        });
+        Ok(())
+    }
+
+    pub fn buy_bait(
+        &mut self,
+        player_id: Id,
+        bait: usize,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let config = self
+            .world
+            .config
+            .baits
+            .get(bait)
+            .ok_or(TransactionError::InvalidBait)?;
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        let shop_distance = distance_to_nearest(&self.world.config.fish_shops, player.pos.pos);
+        if shop_distance > SHOPPING_DISTANCE {
+            return Err(TransactionError::TooFarFromShop);
+        }
+        if player.money < config.cost {
+            return Err(TransactionError::NotEnoughMoney);
+        }
+        player.money -= config.cost;
+        if player.baits.len() <= bait {
+            player.baits.resize(bait + 1, 0);
+        }
+        player.baits[bait] += config.amount;
+        events.push(Event::BoughtBait {
+            player: player_id,
+            bait,
+        });
+        events.push(Event::Sound {
+            player: None,
+            sound_type: SoundType::SellFish,
+            pos: player.pos.pos,
+        });
+        Ok(())
+    }
+
+    pub fn buy_rod(
+        &mut self,
+        player_id: Id,
+        level: u8,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let rod = self
+            .world
+            .config
+            .rod_types
+            .get(level as usize)
+            .ok_or(TransactionError::InvalidRod)?;
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        if player.rod_level >= level {
+            return Err(TransactionError::AlreadyOwned);
+        }
+        let shop_distance = distance_to_nearest(&self.world.config.fish_shops, player.pos.pos);
+        if shop_distance > SHOPPING_DISTANCE {
+            return Err(TransactionError::TooFarFromShop);
+        }
+        if player.money < rod.cost {
+            return Err(TransactionError::NotEnoughMoney);
+        }
+        player.money -= rod.cost;
+        player.rod_level = level;
+        events.push(Event::BoughtRod {
+            player: player_id,
+            level,
+        });
+        events.push(Event::Sound {
+            player: None,
+            sound_type: SoundType::UpgradeBoat,
+            pos: player.pos.pos,
+        });
+        Ok(())
+    }
+
+    pub fn buy_boat(
+        &mut self,
+        player_id: Id,
+        level: u8,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let boat_type = (level as usize)
+            .checked_sub(1)
+            .and_then(|index| self.world.config.boat_types.get(index))
+            .ok_or(TransactionError::InvalidBoat)?;
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        if player.boat_level >= level {
+            return Err(TransactionError::AlreadyOwned);
+        }
+        if distance_to_nearest(&boat_type.shops, player.pos.pos) > SHOPPING_DISTANCE {
+            return Err(TransactionError::TooFarFromShop);
+        }
+        if player.money < boat_type.cost {
+            return Err(TransactionError::NotEnoughMoney);
+        }
+        player.money -= boat_type.cost;
+        player.boat_level = level;
+        events.push(Event::BoughtBoat {
+            player: player_id,
+            level,
+        });
+        events.push(Event::Sound {
+            player: None,
+            sound_type: SoundType::UpgradeBoat,
+            pos: player.pos.pos,
+        });
+        Ok(())
+    }
+}
+
+#[test]
+fn test_release_fish() {
+    let mut model = Model::init();
+    let mut events = Vec::new();
+    let player_id = model.id_gen.gen();
+    let mut player = Player::new(player_id, Vec2::ZERO);
+    let caught = CaughtFishInstance::average(0, &model.world.fish_configs.configs[0]);
+    let other = CaughtFishInstance::average(1, &model.world.fish_configs.configs[1]);
+    player.inventory = vec![caught, other, caught];
+    model.players.insert(player);
+
+    // Only the released slot leaves the inventory
+    model
+        .release_fish(player_id, 1, Vec2::ZERO, &mut events)
+        .unwrap();
+    assert_eq!(
+        model.players.get(&player_id).unwrap().inventory,
+        vec![caught, caught],
+    );
+
+    // A full species does not grow
+    let population = model.population(0);
+    model
+        .release_fish(player_id, 0, Vec2::ZERO, &mut events)
+        .unwrap();
+    assert_eq!(model.population(0), population);
+
+    let fish = model.fishes.iter().find(|fish| fish.index == 0).unwrap().id;
+    model.fishes.remove(&fish);
+    model
+        .release_fish(player_id, 0, vec2(100.0, 0.0), &mut events)
+        .unwrap();
+    assert_eq!(model.population(0), population);
+    assert!(model
+        .fishes
+        .iter()
+        .any(|fish| fish.index == 0 && fish.pos.pos.len() <= MAX_RELEASE_DISTANCE));
+
+    assert_eq!(
+        model.release_fish(player_id, 0, Vec2::ZERO, &mut events),
+        Err(TransactionError::InvalidSlot),
+    );
+    assert_eq!(
+        model.release_fish(player_id, usize::MAX, Vec2::ZERO, &mut events),
+        Err(TransactionError::InvalidSlot),
+    );
+}
+
+#[test]
+fn test_unknown_player() {
+    let mut model = Model::init();
+    let mut events = Vec::new();
+    let nobody = model.id_gen.gen();
+    let unknown = Err(TransactionError::UnknownPlayer);
+    assert_eq!(
+        model.release_fish(nobody, 0, Vec2::ZERO, &mut events),
+        unknown
+    );
+    assert_eq!(model.sell_fish(nobody, 0, &mut events), unknown);
+    assert_eq!(model.buy_bait(nobody, 0, &mut events), unknown);
+    assert_eq!(model.buy_rod(nobody, 1, &mut events), unknown);
+    assert_eq!(model.buy_boat(nobody, 1, &mut events), unknown);
+    assert!(events.is_empty());
+}
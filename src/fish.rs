@@ -1,11 +1,15 @@
 use super::*;
 
-#[derive(HasId, PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[derive(HasId, PartialEq, Debug, Clone, Serialize, Deserialize, Diff)]
 pub struct Fish {
+    #[diff = "eq"]
     pub id: Id,
+    #[diff = "eq"]
     pub index: usize,
     pub pos: Position,
+    #[diff = "eq"]
     pub target_pos: Vec2<f32>,
+    #[diff = "eq"]
     pub scared: bool,
 }
 
@@ -26,6 +30,9 @@ impl Fish {
     }
 }
 
+const FLOCK_DISTANCE: f32 = 3.0;
+const SCARE_DISTANCE: f32 = 4.0;
+
 pub struct FishMovementUpdate {
     vel: Vec2<f32>,
 }
@@ -41,15 +48,20 @@ impl Model {
         }
         result.sub(fish.pos.pos) / 20.0 * delta_time
     }
-    pub fn avoid(fish: &Fish, delta_time: f32, nearby_fish: &Vec<&Fish>) -> Vec2<f32> {
+    pub fn avoid(
+        world: &World,
+        fish: &Fish,
+        delta_time: f32,
+        nearby_fish: &Vec<&Fish>,
+    ) -> Vec2<f32> {
         let mut result: Vec2<f32> = Vec2::ZERO;
-        let fish_size = FishConfigs::get().configs[fish.index].size;
+        let fish_size = world.fish_configs.configs[fish.index].size;
         for fish2 in nearby_fish {
             if fish2.pos.pos.sub(fish.pos.pos).len() < fish_size {
                 result -= fish2.pos.pos - fish.pos.pos;
             }
         }
-        let fish_config = &FishConfigs::get().configs[fish.index];
+        let fish_config = &world.fish_configs.configs[fish.index];
         result * delta_time * fish_size
     }
     pub fn match_velocity(fish: &Fish, delta_time: f32, nearby_fish: &Vec<&Fish>) -> Vec2<f32> {
@@ -62,16 +74,26 @@ impl Model {
         }
         result.sub(fish.pos.vel) / 8.0 * delta_time
     }
-    pub fn congregate(fish: &Fish, delta_time: f32) -> Vec2<f32> {
-        let height = Map::get_height(Map::get(), fish.pos.pos);
+    pub fn congregate(world: &World, fish: &Fish, delta_time: f32) -> Vec2<f32> {
+        let height = world.map.get_height(fish.pos.pos);
         if height > -0.3 {
             let delta = 0.1;
-            let hx = Map::get().get_height(fish.pos.pos + vec2(delta, 0.0));
-            let hy = Map::get().get_height(fish.pos.pos + vec2(0.0, delta));
+            let hx = world.map.get_height(fish.pos.pos + vec2(delta, 0.0));
+            let hy = world.map.get_height(fish.pos.pos + vec2(0.0, delta));
             let gradient = vec2(height - hx, height - hy);
             return gradient.normalize_or_zero() * 10.0;
         }
-        let spawn_circle = &FishConfigs::get().configs[fish.index].spawn_circle;
+        let spawn_circle = &world.fish_configs.configs[fish.index].spawn_circle;
+        if let Some(region) = &spawn_circle.region {
+            if region.contains(&world.map, fish.pos.pos) {
+                return Vec2::ZERO;
+            }
+            // Outside our designated spawn area - head home
+            let home = region
+                .closest_point(&world.map, fish.pos.pos)
+                .unwrap_or(spawn_circle.center);
+            return (home - fish.pos.pos) * delta_time;
+        }
         let dist = spawn_circle.center.sub(fish.pos.pos);
         // We're inside our spawn circle - follow behavior rules
         if dist.len() < spawn_circle.radius {
@@ -89,12 +111,19 @@ impl Model {
         Vec2::ZERO
     }
 
-    pub fn currents(fish: &Fish, delta_time: f32, time: f32) -> Vec2<f32> {
-        let spawn_circle = &FishConfigs::get().configs[fish.index].spawn_circle;
+    pub fn currents(
+        world: &World,
+        fish: &Fish,
+        delta_time: f32,
+        time: f32,
+        weather: Weather,
+    ) -> Vec2<f32> {
+        let spawn_circle = &world.fish_configs.configs[fish.index].spawn_circle;
         let dist = spawn_circle.center.sub(fish.pos.pos);
+        let wind = weather.wind(time) * delta_time;
         match spawn_circle.behavior {
-            FishBehavior::Idle => Vec2::ZERO,
-            FishBehavior::Kuviseal => dist,
+            FishBehavior::Idle => wind,
+            FishBehavior::Kuviseal => dist + wind,
             FishBehavior::Space => Vec2 {
                 x: time.cos(),
                 y: time.sin(),
@@ -110,7 +139,8 @@ impl Model {
                             x: -dist.y,
                             y: dist.x,
                         } / spawn_circle.radius
-                            / 2.0;
+                            / 2.0
+                            + wind;
                     }
                 }
                 Vec2 {
@@ -118,6 +148,7 @@ impl Model {
                     y: -dist.x,
                 } / spawn_circle.radius
                     / 2.0
+                    + wind
             }
             FishBehavior::Chaos => {
                 let scaled_pos = fish.pos.pos / 5.0
@@ -129,39 +160,43 @@ impl Model {
                     x: scaled_pos.x.cos() + scaled_pos.y.cos(),
                     y: scaled_pos.x.sin() + scaled_pos.y.sin(),
                 } * delta_time
+                    + wind
             }
         }
     }
     pub fn update_fishes(&mut self, delta_time: f32, events: &mut Vec<Event>) {
-        let reeling_fishes: HashSet<Id> = self
+        let reeling_fishes: HashMap<Id, Vec2<f32>> = self
             .players
             .iter()
             .flat_map(|player| {
-                if let FishingState::Reeling { fish, .. } | FishingState::PreReeling { fish, .. } =
-                    player.fishing_state
-                {
-                    Some(fish)
-                } else {
-                    None
-                }
+                let fish = player.fishing_state.hooked_fish()?;
+                Some((fish, player.fishing_state.bobber_pos()?))
             })
             .collect();
 
+        let mut fish_list: Vec<&Fish> = self.fishes.iter().collect();
+        // A fixed order keeps seeded ticks reproducible
+        fish_list.sort_by_key(|fish| fish.id.0);
+        let fish_hash =
+            SpatialHash::build(FLOCK_DISTANCE, fish_list.iter().map(|fish| fish.pos.pos));
+
         let mut updates: HashMap<Id, FishMovementUpdate> = HashMap::new();
-        for fish in &self.fishes {
-            let nearby_fish: Vec<&Fish> = self
-                .fishes
-                .iter()
+        for fish in &fish_list {
+            let nearby_fish: Vec<&Fish> = fish_hash
+                .query(fish.pos.pos, FLOCK_DISTANCE)
+                .map(|i| fish_list[i])
                 .filter(|f| {
-                    f.pos.pos.sub(fish.pos.pos).len() < 3.0
+                    f.pos.pos.sub(fish.pos.pos).len() < FLOCK_DISTANCE
                         && f.id() != fish.id()
                         && f.index == fish.index
                 })
                 .collect();
 
-            let v0 = Self::currents(fish, delta_time, self.time);
+            let v0 = Self::currents(&self.world, fish, delta_time, self.time, self.weather);
 
-            let behavior = &FishConfigs::get().configs[fish.index].spawn_circle.behavior;
+            let behavior = &self.world.fish_configs.configs[fish.index]
+                .spawn_circle
+                .behavior;
             let v = match behavior {
                 FishBehavior::Space | FishBehavior::Land | FishBehavior::Kuviseal => {
                     // no boids in space!
@@ -169,9 +204,9 @@ impl Model {
                 }
                 _ => {
                     let v1 = Self::flock(fish, delta_time, &nearby_fish);
-                    let v2 = Self::avoid(fish, delta_time, &nearby_fish);
+                    let v2 = Self::avoid(&self.world, fish, delta_time, &nearby_fish);
                     let v3 = Self::match_velocity(fish, delta_time, &nearby_fish);
-                    let v4 = Self::congregate(fish, delta_time);
+                    let v4 = Self::congregate(&self.world, fish, delta_time);
                     v0 + v1 + v2 + v3 + v4
                 }
             };
@@ -188,8 +223,51 @@ impl Model {
 
             updates.insert(fish.id, FishMovementUpdate { vel: v });
         }
-        for fish in &mut self.fishes {
-            let behavior = &FishConfigs::get().configs[fish.index].spawn_circle.behavior;
+        let fish_ids: Vec<Id> = fish_list.iter().map(|fish| fish.id).collect();
+
+        let moving_players: Vec<Vec2<f32>> = self
+            .players
+            .iter()
+            .filter(|player| player.pos.vel.len() >= 1.0)
+            .map(|player| player.pos.pos)
+            .collect();
+        let player_hash = SpatialHash::build(SCARE_DISTANCE, moving_players.iter().copied());
+        struct Bobber {
+            player: Id,
+            pos: Vec2<f32>,
+            attract_distance: f32,
+            modifiers: BiteModifiers,
+        }
+        let mut bobbers: Vec<Bobber> = self
+            .players
+            .iter()
+            .filter_map(|player| match player.fishing_state {
+                FishingState::Waiting(pos) => Some(Bobber {
+                    player: player.id,
+                    pos,
+                    attract_distance: self.world.config.rod(player.rod_level).attract_distance,
+                    modifiers: self.bite_modifiers(player.id),
+                }),
+                _ => None,
+            })
+            .collect();
+        bobbers.sort_by_key(|bobber| bobber.player.0);
+        let max_attract_distance = self
+            .world
+            .config
+            .rod_types
+            .iter()
+            .map(|rod| rod.attract_distance)
+            .fold(0.0, f32::max);
+        let bobber_hash = SpatialHash::build(
+            max_attract_distance,
+            bobbers.iter().map(|bobber| bobber.pos),
+        );
+        for id in fish_ids {
+            let Some(fish) = self.fishes.get_mut(&id) else { continue };
+            let behavior = &self.world.fish_configs.configs[fish.index]
+                .spawn_circle
+                .behavior;
             if *behavior == FishBehavior::Land {
                 if let Some(update) = updates.get(&fish.id) {
                     fish.pos.vel += update.vel;
@@ -200,23 +278,22 @@ impl Model {
             }
             // // Scaring
             let run_away_distance = 5.0;
-            for player in &self.players {
-                if player.pos.vel.len() < 1.0 {
-                    continue;
-                }
-                let scare_distance = 4.0;
-                if (fish.pos.pos - player.pos.pos).len() < scare_distance {
-                    fish.target_pos = player.pos.pos
-                        + (fish.pos.pos - player.pos.pos).normalize_or_zero() * run_away_distance;
+            for i in player_hash.query(fish.pos.pos, SCARE_DISTANCE) {
+                let player_pos = moving_players[i];
+                if (fish.pos.pos - player_pos).len() < SCARE_DISTANCE {
+                    fish.target_pos = player_pos
+                        + (fish.pos.pos - player_pos).normalize_or_zero() * run_away_distance;
                     fish.scared = true;
                 }
             }
-            if reeling_fishes.contains(&fish.id) {
+            if let Some(&bobber_pos) = reeling_fishes.get(&fish.id) {
+                // Hooked fish are dragged around by the line
+                fish.pos.pos = bobber_pos;
                 fish.pos.vel = Vec2::ZERO;
                 fish.pos.w = 0.0;
                 continue;
             }
-            let max_speed = if Map::get().get_height(fish.pos.pos) < 0.0 {
+            let max_speed = if self.world.map.get_height(fish.pos.pos) < 0.0 {
                 1000.0
             } else {
                 0.5
@@ -241,30 +318,41 @@ impl Model {
             }
             // Attraction
             let mut attracted = false;
-            for player in &mut self.players {
-                let bobber_attract_distance = 2.2;
-                if let FishingState::Waiting(bobber_pos) = player.fishing_state {
-                    if (bobber_pos - fish.pos.pos).len() < bobber_attract_distance {
+            for i in bobber_hash.query(fish.pos.pos, max_attract_distance) {
+                let bobber = &bobbers[i];
+                let (bobber_pos, modifiers) = (bobber.pos, &bobber.modifiers);
+                let Some(player) = self.players.get_mut(&bobber.player) else { continue };
+                let bite = &self.world.fish_configs.configs[fish.index].bite;
+                if player.fishing_state == FishingState::Waiting(bobber_pos)
+                    && bite.attracted(modifiers)
+                {
+                    if (bobber_pos - fish.pos.pos).len() < bobber.attract_distance {
                         attracted = true;
                         fish.target_pos = fish.pos.pos;
                         fish.pos.rot = normalize_angle((bobber_pos - fish.pos.pos).arg());
                         fish.pos.w = 0.0;
-                        if global_rng().gen_bool(0.005) {
+                        if self.rng.gen_bool(bite.chance(modifiers, delta_time)) {
+                            // The bait is gone even if the fish gets away
+                            if let Some(bait) = player.active_bait() {
+                                player.baits[bait] -= 1;
+                            }
                             fish.pos.pos = bobber_pos;
                             fish.target_pos = bobber_pos
                                 + vec2(run_away_distance, 0.0)
-                                    .rotate(global_rng().gen_range(0.0..2.0 * f32::PI));
+                                    .rotate(self.rng.gen_range(0.0..2.0 * f32::PI));
                             fish.scared = true;
                             player.fishing_state = FishingState::PreReeling {
                                 fish: fish.id,
                                 bobber_pos,
                             };
+                            self.struggle_checks
+                                .insert(player.id, StruggleCheck::new(self.time, bobber_pos));
                             events.push(Event::Reel {
                                 player: player.id,
                                 fish: fish.id,
                             });
                             events.push(Event::Sound {
-                                player: Id(u64::MAX),
+                                player: None,
                                 sound_type: SoundType::Splash,
                                 pos: fish.pos.pos,
                             });
@@ -312,6 +400,10 @@ fn easy_in_out_quad(x: f32) -> f32 {
 }
 
 impl Game {
+    pub fn fish_assets(&self, fish_type: FishType) -> &FishAssets {
+        &self.assets.fishes[self.fish_asset_indices[fish_type]]
+    }
+
     pub fn draw_fishes(&self, framebuffer: &mut ugli::Framebuffer) {
         let model = self.model.get();
         #[derive(ugli::Vertex)]
@@ -323,10 +415,12 @@ impl Game {
         for fish in &model.fishes {
             let Some(pos) = self.interpolated.get(&fish.id) else { continue };
             let pos = pos.get();
-            let mut height = Map::get().get_height(pos.pos).max(-0.2);
+            let mut height = self.world.map.get_height(pos.pos).max(-0.2);
             let mut rot_y = 0.0;
             let mut star_rot = 0.0;
-            let behavior = &self.assets.fishes[fish.index].config.spawn_circle.behavior;
+            let behavior = &self.world.fish_configs.configs[fish.index]
+                .spawn_circle
+                .behavior;
             let mut stand_up = false;
             // fish flopping
             match behavior {
@@ -349,7 +443,7 @@ impl Game {
                 }
             }
 
-            let texture = &self.assets.fishes[fish.index].texture;
+            let texture = &self.fish_assets(fish.index).texture;
             let matrix = Mat4::translate(
                 // {
                 //     let mut pos = pos.pos;
@@ -382,7 +476,7 @@ impl Game {
             });
         }
         for (index, instances) in instances {
-            let texture = &self.assets.fishes[index].texture;
+            let texture = &self.fish_assets(index).texture;
             ugli::draw(
                 framebuffer,
                 &self.assets.shaders.fish,
@@ -394,6 +488,8 @@ impl Game {
                 (
                     ugli::uniforms! {
                         u_texture: texture,
+                        u_light: self.light(),
+                        u_fog_density: self.fog_density,
                     },
                     geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
                 ),
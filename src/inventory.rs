@@ -41,7 +41,7 @@ impl Game {
                 (pos, rot, 0.3)
             };
 
-            let texture = &self.assets.fishes[fish.index].texture;
+            let texture = &self.fish_assets(fish.index).texture;
             let fish_card = draw_2d::TexturedQuad::new(
                 AABB::point(Vec2::ZERO).extend_symmetric(
                     vec2(texture.size().x as f32 / texture.size().y as f32, 1.0) * scale,
@@ -53,15 +53,16 @@ impl Game {
             self.geng.draw_2d(framebuffer, &camera, &fish_card);
         }
 
-        let size =
-            (self.inventory.len() as f32 - 1.0) * 10.0 / self.assets.config.inventory_size as f32;
+        let size = (self.player.inventory.len() as f32 - 1.0) * 10.0
+            / self.world.config.inventory_size as f32;
         let mut hovered = None;
-        for (index, &fish) in self.inventory.iter().enumerate() {
+        for (index, fish) in self.player.inventory.iter().enumerate() {
             let pos = vec2(
-                (index as f32 / (self.inventory.len() - 1).max(1) as f32) * size - size / 2.0,
+                (index as f32 / (self.player.inventory.len() - 1).max(1) as f32) * size - size / 2.0,
                 -camera.fov / 2.0,
             );
-            let texture = &self.assets.fishes[fish].texture;
+            // Borrows only the assets, since the texture is kept after the loop
+            let texture = &self.assets.fishes[self.fish_asset_indices[fish.fish_type]].texture;
             let fish_card = draw_2d::TexturedQuad::new(
                 AABB::point(Vec2::ZERO)
                     .extend_symmetric(vec2(texture.size().x as f32 / texture.size().y as f32, 1.0)),
@@ -81,14 +82,16 @@ impl Game {
         self.hovered_inventory_slot = None;
         if let Some((index, texture, pos)) = hovered {
             self.hovered_inventory_slot = Some(index);
-            let fish = &self.assets.fishes[self.inventory[index]];
+            let fish = &self.player.inventory[index];
+            let config = &self.world.fish_configs.configs[fish.fish_type];
             self.tutorial = if self.can_sell_fish() {
                 format!(
                     "click to sell {} for ${}",
-                    fish.config.name, fish.config.cost,
+                    fish.description(config),
+                    fish.price(config),
                 )
             } else {
-                format!("click to release {}", fish.config.name)
+                format!("click to release {}", fish.description(config))
             };
             self.tutorial_timer = 0.1;
             let fish_card = draw_2d::TexturedQuad::new(
@@ -102,13 +105,13 @@ impl Game {
             self.geng.draw_2d(framebuffer, &camera, &fish_card);
         }
         if self.hovered_inventory_slot.is_some() && last_hovered_inventory_slot.is_none() {
-            self.play_sound_for_everyone(self.player.pos.pos, SoundType::ShowFish);
+            self.play_sound_for_everyone(self.player.pos.pos, ClientSound::ShowFish);
         }
 
         self.draw_text(
             framebuffer,
             &camera,
-            &format!("$ {}", self.money),
+            &format!("$ {}", self.player.money),
             vec2(0.0, camera.fov / 2.0 - 1.0),
         );
         self.draw_text(
@@ -116,12 +119,36 @@ impl Game {
             &camera,
             &format!(
                 "fishdex: {}/{}",
-                self.fishdex.len(),
-                self.assets.fishes.len(),
+                self.player.fishdex.len(),
+                self.world.fish_configs.configs.len(),
             ),
             vec2(5.0, camera.fov / 2.0 - 1.0),
         );
+        self.draw_clock(framebuffer, &camera);
+        self.draw_quests(framebuffer, &camera);
 
+        if let Some(bait) = self.player.active_bait() {
+            self.draw_text(
+                framebuffer,
+                &camera,
+                &format!(
+                    "{} x{}",
+                    self.world.config.baits[bait].name, self.player.baits[bait],
+                ),
+                vec2(-5.0, camera.fov / 2.0 - 2.0),
+            );
+        }
+
+        if self.hovered_inventory_slot.is_none()
+            && self.can_sell_fish()
+            && self.tutorial_timer < 0.0
+        {
+            self.tutorial = self.fish_shop_text();
+            self.tutorial_timer = 0.1;
+        }
+        if !self.can_sell_fish() {
+            self.show_reports = false;
+        }
         if let Some((_, config)) = self.is_hovering_boat_shop() {
             self.tutorial = format!("click to buy {} for ${}", config.name, config.cost);
             self.tutorial_timer = 0.1;
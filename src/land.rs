@@ -1,25 +1,27 @@
 use super::*;
 
-const DEEPEST_DEPTH: f32 = -1.5;
-const HIGHEST_LAND: f32 = 0.5;
-pub const SIZE: f32 = 100.0;
+use anyhow::Context as _;
+
 pub const SHORE_HEIGHT: f32 = -0.2;
+pub const DEEP_SEA_HEIGHT: f32 = -1.25;
 
 pub struct MapGeometry {
+    pub heightmap: ugli::Texture,
+    pub colormap: ugli::Texture,
     pub land: ugli::VertexBuffer<ObjVertex>,
     pub edge: ugli::VertexBuffer<ObjVertex>,
     pub water: ugli::VertexBuffer<ObjVertex>,
-    pub edge_segments: Vec<[Vec2<f32>; 2]>,
-    pub deep_segments: Vec<[Vec2<f32>; 2]>,
-    pub shore_segments: Vec<[Vec2<f32>; 2]>,
+    pub edge_segments: SegmentGrid,
+    pub deep_segments: SegmentGrid,
+    pub shore_segments: SegmentGrid,
 }
 
-fn create_triangles() -> Vec<[Vec2<f32>; 3]> {
+fn create_triangles(size: f32) -> Vec<[Vec2<f32>; 3]> {
     let mut triangles = Vec::new();
     const N: i32 = 128;
     for x in (-N..N).skip(1).step_by(2) {
         for y in (-N..N).skip(1).step_by(2) {
-            let vertex = |x, y| vec2(x as f32 / N as f32 * SIZE, y as f32 / N as f32 * SIZE);
+            let vertex = |x, y| vec2(x as f32 / N as f32 * size, y as f32 / N as f32 * size);
             let mut quad = |dx, dy| {
                 triangles.push([vertex(x, y), vertex(x + dx, y), vertex(x + dx, y + dy)]);
                 triangles.push([vertex(x, y), vertex(x + dx, y + dy), vertex(x, y + dy)]);
@@ -33,20 +35,20 @@ fn create_triangles() -> Vec<[Vec2<f32>; 3]> {
     triangles
 }
 
-pub fn create_map_geometry(geng: &Geng, assets: &Assets) -> MapGeometry {
+pub fn create_map_geometry(geng: &Geng, assets: &Assets, map: &Map) -> MapGeometry {
     let mut edge = Vec::new();
     let mut water = Vec::new();
     let mut land = Vec::new();
     let mut edge_segments = Vec::new();
-    for tri in create_triangles() {
+    for tri in create_triangles(map.size) {
         let a = tri[0];
         let b = tri[1];
         let c = tri[2];
         let mut zeros = Vec::new();
         let mut water_vs = Vec::new();
         let mut check = |a: Vec2<f32>, b: Vec2<f32>| {
-            let av = Map::get().get_channel_value(3, a);
-            let bv = Map::get().get_channel_value(3, b);
+            let av = map.get_channel_value(3, a);
+            let bv = map.get_channel_value(3, b);
             let mut a = (a, av);
             let mut b = (b, bv);
             if a.1 >= 0.5 {
@@ -95,12 +97,12 @@ pub fn create_map_geometry(geng: &Geng, assets: &Assets) -> MapGeometry {
                 for v in [water_vs[0], vs[0], vs[1]] {
                     water.push(ObjVertex {
                         a_v: v.extend(0.0),
-                        a_uv: v.map(|x| (x + SIZE) / (2.0 * SIZE)),
+                        a_uv: map.uv(v),
                         a_vn: Vec3::ZERO,
                     });
                     land.push(ObjVertex {
-                        a_v: v.extend(Map::get().get_height(v)),
-                        a_uv: v.map(|x| (x + SIZE) / (2.0 * SIZE)),
+                        a_v: v.extend(map.get_height(v)),
+                        a_uv: map.uv(v),
                         a_vn: Vec3::ZERO,
                     });
                 }
@@ -108,54 +110,49 @@ pub fn create_map_geometry(geng: &Geng, assets: &Assets) -> MapGeometry {
         }
     }
 
-    let mut shore_segments = Vec::new();
-    for tri in create_triangles() {
-        let a = tri[0];
-        let b = tri[1];
-        let c = tri[2];
-        let mut zeros = Vec::new();
-        let mut check = |a: Vec2<f32>, b: Vec2<f32>| {
-            let av = Map::get().get_height(a);
-            let bv = Map::get().get_height(b);
-            let mut a = (a, av);
-            let mut b = (b, bv);
-            if a.1 > b.1 {
-                mem::swap(&mut a, &mut b);
-            }
-            let mid = SHORE_HEIGHT;
-            if a.1 < mid && b.1 >= mid {
-                let t = (mid - a.1) / (b.1 - a.1);
-                let z = a.0 + t * (b.0 - a.0);
-                zeros.push(z);
-            }
-        };
-        check(a, b);
-        check(b, c);
-        check(c, a);
-        if zeros.len() == 2 {
-            let z1 = zeros[0];
-            let z2 = zeros[1];
-            shore_segments.push([z1, z2]);
-        }
+    MapGeometry {
+        heightmap: create_texture(geng, &map.image),
+        colormap: create_texture(geng, &map.image_color),
+        land: ugli::VertexBuffer::new_static(geng.ugli(), land),
+        edge: ugli::VertexBuffer::new_static(geng.ugli(), edge),
+        water: ugli::VertexBuffer::new_static(geng.ugli(), water),
+        edge_segments: SegmentGrid::new(edge_segments, SegmentGrid::DEFAULT_CELL_SIZE),
+        shore_segments: SegmentGrid::new(
+            contour_segments(map.size, |pos| map.get_height(pos), SHORE_HEIGHT),
+            SegmentGrid::DEFAULT_CELL_SIZE,
+        ),
+        deep_segments: SegmentGrid::new(
+            contour_segments(map.size, |pos| map.get_height(pos), DEEP_SEA_HEIGHT),
+            SegmentGrid::DEFAULT_CELL_SIZE,
+        ),
     }
+}
+
+fn create_texture(geng: &Geng, image: &image::RgbaImage) -> ugli::Texture {
+    let size = vec2(image.width() as usize, image.height() as usize);
+    ugli::Texture::new_with(geng.ugli(), size, |pos| {
+        let pixel = image.get_pixel(pos.x as u32, (size.y - 1 - pos.y) as u32);
+        let [r, g, b, a] = pixel.0.map(|x| x as f32 / 0xff as f32);
+        Rgba::new(r, g, b, a)
+    })
+}
 
-    let mut deep_segments = Vec::new();
-    for tri in create_triangles() {
+// Segments along which the value crosses the given level
+pub fn contour_segments(size: f32, value: impl Fn(Vec2<f32>) -> f32, level: f32) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    for tri in create_triangles(size) {
         let a = tri[0];
         let b = tri[1];
         let c = tri[2];
         let mut zeros = Vec::new();
         let mut check = |a: Vec2<f32>, b: Vec2<f32>| {
-            let av = Map::get().get_height(a);
-            let bv = Map::get().get_height(b);
-            let mut a = (a, av);
-            let mut b = (b, bv);
+            let mut a = (a, value(a));
+            let mut b = (b, value(b));
             if a.1 > b.1 {
                 mem::swap(&mut a, &mut b);
             }
-            let mid = -1.25;
-            if a.1 < mid && b.1 >= mid {
-                let t = (mid - a.1) / (b.1 - a.1);
+            if a.1 < level && b.1 >= level {
+                let t = (level - a.1) / (b.1 - a.1);
                 let z = a.0 + t * (b.0 - a.0);
                 zeros.push(z);
             }
@@ -164,87 +161,72 @@ pub fn create_map_geometry(geng: &Geng, assets: &Assets) -> MapGeometry {
         check(b, c);
         check(c, a);
         if zeros.len() == 2 {
-            let z1 = zeros[0];
-            let z2 = zeros[1];
-            deep_segments.push([z1, z2]);
+            segments.push([zeros[0], zeros[1]]);
         }
     }
-
-    MapGeometry {
-        land: ugli::VertexBuffer::new_static(geng.ugli(), land),
-        edge: ugli::VertexBuffer::new_static(geng.ugli(), edge),
-        water: ugli::VertexBuffer::new_static(geng.ugli(), water),
-        edge_segments,
-        shore_segments,
-        deep_segments,
-    }
-}
-
-fn to_segment(p1: Vec2<f32>, p2: Vec2<f32>, point: Vec2<f32>) -> Vec2<f32> {
-    if Vec2::dot(point - p1, p2 - p1) < 0.0 {
-        return p1 - point;
-    }
-    if Vec2::dot(point - p2, p1 - p2) < 0.0 {
-        return p2 - point;
-    }
-    let n = (p2 - p1).rotate_90();
-    // dot(point + n * t - p1, n) = 0
-    // dot(point - p1, n) + dot(n, n) * t = 0
-    let t = Vec2::dot(p1 - point, n) / Vec2::dot(n, n);
-    n * t
-}
-
-pub fn vec_to(segments: &[[Vec2<f32>; 2]], point: Vec2<f32>) -> Vec2<f32> {
     segments
-        .iter()
-        .map(|&[p1, p2]| to_segment(p1, p2, point))
-        .min_by_key(|v| r32(v.len_sqr()))
-        .unwrap()
 }
 
 pub struct Map {
+    // The map covers `-size..size` on both axes
+    pub size: f32,
+    pub deepest_depth: f32,
+    pub highest_land: f32,
     image: image::RgbaImage,
     image_color: image::RgbaImage,
 }
 
-static mut MAP: Option<Map> = None;
-
 impl Map {
-    pub fn get() -> &'static Map {
-        unsafe { MAP.get_or_insert_with(Map::load) }
+    pub fn new(
+        manifest: &MapManifest,
+        image: image::RgbaImage,
+        image_color: image::RgbaImage,
+    ) -> Self {
+        Self {
+            size: manifest.size,
+            deepest_depth: manifest.deepest_depth,
+            highest_land: manifest.highest_land,
+            image,
+            image_color,
+        }
     }
-    pub fn load() -> Self {
-        let image = image::load(
-            std::io::Cursor::new(include_bytes!("../static/assets/map.png")),
-            image::ImageFormat::Png,
-        )
-        .unwrap()
-        .into_rgba8();
-        let image_color = image::load(
-            std::io::Cursor::new(include_bytes!("../static/assets/map_color.png")),
-            image::ImageFormat::Png,
-        )
-        .unwrap()
-        .into_rgba8();
-        Self { image, image_color }
+    pub fn from_images(
+        manifest: &MapManifest,
+        height_png: &[u8],
+        color_png: &[u8],
+    ) -> anyhow::Result<Self> {
+        let image = image::load(std::io::Cursor::new(height_png), image::ImageFormat::Png)
+            .context("Failed to decode the heightmap")?
+            .into_rgba8();
+        let image_color = image::load(std::io::Cursor::new(color_png), image::ImageFormat::Png)
+            .context("Failed to decode the colormap")?
+            .into_rgba8();
+        Ok(Self::new(manifest, image, image_color))
+    }
+    pub fn uv(&self, pos: Vec2<f32>) -> Vec2<f32> {
+        pos.map(|x| (x + self.size) / (2.0 * self.size))
     }
     pub fn get_dimensions(&self) -> (u32, u32) {
         self.image.dimensions()
     }
     pub fn get_height(&self, pos: Vec2<f32>) -> f32 {
-        self.get_channel_value(0, pos) * (HIGHEST_LAND - DEEPEST_DEPTH) + DEEPEST_DEPTH
+        self.get_channel_value(0, pos) * (self.highest_land - self.deepest_depth)
+            + self.deepest_depth
     }
     pub fn get_is_void(&self, pos: Vec2<f32>) -> bool {
         self.get_channel_value(3, pos) < 0.9
     }
+    pub fn is_water(&self, pos: Vec2<f32>) -> bool {
+        self.get_height(pos) < SHORE_HEIGHT && self.get_channel_value(3, pos) > 0.5
+    }
     pub fn is_ice(&self, pos: Vec2<f32>) -> bool {
-        let uv = pos.map(|x| ((x + SIZE) / (2.0 * SIZE)) * self.image_color.width() as f32);
+        let uv = self.uv(pos) * self.image_color.width() as f32;
         let color = self.get_pixel_color(uv.map(|x| x.floor() as i32));
         color.0[2] > color.0[0]
     }
 
     pub fn get_channel_value(&self, channel: usize, pos: Vec2<f32>) -> f32 {
-        let uv = pos.map(|x| ((x + SIZE) / (2.0 * SIZE)) * self.image.width() as f32);
+        let uv = self.uv(pos) * self.image.width() as f32;
         let values: [[f32; 2]; 2] = std::array::from_fn(|dx| {
             std::array::from_fn(|dy| {
                 let color =
@@ -1,7 +1,5 @@
 use super::*;
 
-pub const MAX_LINE_LEN: f32 = 7.0;
-
 pub enum PlayerMovementControl {
     GoTo(Vec2<f32>),
     GoDirection(Vec2<f32>),
@@ -9,42 +7,26 @@ pub enum PlayerMovementControl {
 
 impl Game {
     pub fn update_my_player(&mut self, delta_time: f32) {
-        let in_water = Map::get().get_height(self.player.pos.pos) < SHORE_HEIGHT;
+        let in_water = self.world.map.get_height(self.player.pos.pos) < SHORE_HEIGHT;
 
-        let props = if !in_water {
-            MovementProps {
-                max_speed: 2.0,
-                max_rotation_speed: 2.0,
-                angular_acceleration: 1.0,
-                acceleration: 10.0,
-                water: false,
-            }
-        } else {
-            MovementProps {
-                max_speed: 2.0,
-                max_rotation_speed: 2.0,
-                angular_acceleration: 1.0,
-                acceleration: 1.0,
-                water: true,
-            }
-        };
+        let props = MovementProps::player(in_water).in_weather(self.model.get().weather);
         let mut player_radius = 1.0;
         if self.player.boat_level > 0 && in_water {
             player_radius *=
-                self.assets.config.boat_types[(self.player.boat_level - 1) as usize].scale;
+                self.world.config.boat_types[(self.player.boat_level - 1) as usize].scale;
         }
         self.camera.pos = self
             .player
             .pos
             .pos
-            .extend(Map::get().get_height(self.player.pos.pos).max(0.0) + 0.5);
+            .extend(self.world.map.get_height(self.player.pos.pos).max(0.0) + 0.5);
         if let Some(seated) = self.player.seated {
             if let Some(other) = self.model.get().players.get(&seated.player) {
                 if let Some(pos) = self.interpolated.get(&other.id) {
                     self.camera.pos = pos.get().pos.extend(0.0);
                 }
                 self.player.pos.pos = other.pos.pos;
-                if Map::get().get_height(other.pos.pos) > SHORE_HEIGHT {
+                if self.world.map.get_height(other.pos.pos) > SHORE_HEIGHT {
                     self.player.seated = None;
                 }
             } else {
@@ -99,7 +81,7 @@ impl Game {
                     if other_player.seated.is_some() {
                         continue;
                     }
-                    let land = |pos| Map::get().get_height(pos) > SHORE_HEIGHT;
+                    let land = |pos| self.world.map.get_height(pos) > SHORE_HEIGHT;
                     if land(other_player.pos.pos) {
                         continue;
                     }
@@ -115,17 +97,24 @@ impl Game {
             }
             // collide with world edge
             if self.player.boat_level < 3 {
-                let to_edge = vec_to(&self.map_geometry.edge_segments, self.player.pos.pos);
-                if to_edge.len() < player_radius {
-                    let n = -to_edge.normalize_or_zero();
-                    let penetration = player_radius - to_edge.len();
-                    self.player.pos.pos += n * penetration;
-                    self.player.pos.vel -= n * Vec2::dot(n, self.player.pos.vel).min(0.0);
+                if let Some(nearest) = self.map_geometry.edge_segments.nearest(self.player.pos.pos)
+                {
+                    let to_edge = nearest.delta;
+                    if to_edge.len() < player_radius {
+                        let n = -to_edge.normalize_or_zero();
+                        let penetration = player_radius - to_edge.len();
+                        self.player.pos.pos += n * penetration;
+                        self.player.pos.vel -= n * Vec2::dot(n, self.player.pos.vel).min(0.0);
+                    }
                 }
             }
             // collide with shore
+            if let Some(nearest) = self
+                .map_geometry
+                .shore_segments
+                .nearest(self.player.pos.pos)
             {
-                let to_shore = vec_to(&self.map_geometry.shore_segments, self.player.pos.pos);
+                let to_shore = nearest.delta;
                 let player_radius = if in_water { player_radius } else { 0.3 };
                 if to_shore.len() < player_radius {
                     let n = -to_shore.normalize_or_zero();
@@ -136,20 +125,23 @@ impl Game {
             }
             // collide with deep sea boundary
             if self.player.boat_level < 2 {
-                let to_deep = vec_to(&self.map_geometry.deep_segments, self.player.pos.pos);
-                let player_radius = if in_water { player_radius } else { 0.3 };
-                if to_deep.len() < player_radius {
-                    self.tutorial = "you need a bigger boat to explore the deep sea".to_owned();
-                    self.tutorial_timer = 5.0;
-                    let n = -to_deep.normalize_or_zero();
-                    let penetration = player_radius - to_deep.len();
-                    self.player.pos.pos += n * penetration;
-                    self.player.pos.vel -= n * Vec2::dot(n, self.player.pos.vel).min(0.0);
+                if let Some(nearest) = self.map_geometry.deep_segments.nearest(self.player.pos.pos)
+                {
+                    let to_deep = nearest.delta;
+                    let player_radius = if in_water { player_radius } else { 0.3 };
+                    if to_deep.len() < player_radius {
+                        self.tutorial = "you need a bigger boat to explore the deep sea".to_owned();
+                        self.tutorial_timer = 5.0;
+                        let n = -to_deep.normalize_or_zero();
+                        let penetration = player_radius - to_deep.len();
+                        self.player.pos.pos += n * penetration;
+                        self.player.pos.vel -= n * Vec2::dot(n, self.player.pos.vel).min(0.0);
+                    }
                 }
             }
             for &pos in itertools::chain![
-                &self.assets.config.fish_shops,
-                self.assets
+                &self.world.config.fish_shops,
+                self.world
                     .config
                     .boat_types
                     .iter()
@@ -166,11 +158,12 @@ impl Game {
             }
         }
 
+        let line_length = self.world.config.rod(self.player.rod_level).line_length;
         if let Some(bobber_pos) = self.player.fishing_state.bobber_pos() {
             let delta_pos = bobber_pos - self.player.pos.pos;
-            if delta_pos.len() > MAX_LINE_LEN {
+            if delta_pos.len() > line_length {
                 self.player.fishing_state = FishingState::Idle;
-                self.play_sound_for_everyone(self.player.pos.pos, SoundType::StopFishing);
+                self.play_sound_for_everyone(self.player.pos.pos, ClientSound::StopFishing);
             }
         }
         if let FishingState::Attached(id) = self.player.fishing_state {
@@ -189,14 +182,14 @@ impl Game {
                             delta_time,
                         );
                     }
-                    if delta_pos.len() > MAX_LINE_LEN {
+                    if delta_pos.len() > line_length {
                         self.player.fishing_state = FishingState::Idle;
-                        self.play_sound_for_everyone(self.player.pos.pos, SoundType::StopFishing);
+                        self.play_sound_for_everyone(self.player.pos.pos, ClientSound::StopFishing);
                     }
                 }
             } else {
                 self.player.fishing_state = FishingState::Idle;
-                self.play_sound_for_everyone(self.player.pos.pos, SoundType::StopFishing);
+                self.play_sound_for_everyone(self.player.pos.pos, ClientSound::StopFishing);
             }
         }
 
@@ -206,16 +199,16 @@ impl Game {
                 let mut ignore = false;
                 match self.player.fishing_state {
                     FishingState::Casting(bobber_pos) => {
-                        let mut sound_type = None;
-                        if Map::get().get_height(bobber_pos) < SHORE_HEIGHT
-                            && Map::get().get_channel_value(3, bobber_pos) > 0.5
-                        {
+                        let mut splashed = false;
+                        if self.world.map.is_water(bobber_pos) {
                             // This is water
                             self.player.fishing_state = FishingState::Waiting(bobber_pos);
-                            sound_type = Some(SoundType::Splash);
+                            splashed = true;
                             self.splashes.push(Splash::new(bobber_pos, 5, 1.5));
                             if self.show_reel_tutorial {
-                                self.tutorial = "left click to reel when the fish bites".to_owned();
+                                self.tutorial =
+                                    "hold left click to reel when the fish bites\nbut don't pull too hard"
+                                        .to_owned();
                                 self.tutorial_timer = 10.0;
                                 self.show_reel_tutorial = false;
                             }
@@ -223,7 +216,7 @@ impl Game {
                             // This is land/space
                             // TODO: make this code self explanatory
                             for fish in &self.model.get().fishes {
-                                if (fish.pos.pos - bobber_pos).len() < 1.0 {
+                                if (fish.pos.pos - bobber_pos).len() < BOBBER_CATCH_RADIUS {
                                     self.caught_fish.insert(CaughtFish {
                                         id: fish.id,
                                         index: fish.index,
@@ -232,7 +225,7 @@ impl Game {
                                         caught_at: fish.pos.pos,
                                     });
                                     self.model.send(Message::Catch(fish.id));
-                                    self.play_sound_for_everyone(fish.pos.pos, SoundType::Ding);
+                                    self.play_sound_for_everyone(fish.pos.pos, ClientSound::Ding);
                                 }
                             }
                             self.player.fishing_state = FishingState::Idle;
@@ -248,7 +241,7 @@ impl Game {
                                     self.player.fishing_state = FishingState::Idle;
                                     self.play_sound_for_everyone(
                                         self.player.pos.pos,
-                                        SoundType::StopFishing,
+                                        ClientSound::StopFishing,
                                     );
                                 } else {
                                     self.player.fishing_state =
@@ -258,15 +251,13 @@ impl Game {
                                 }
                             }
                         }
-                        if let Some(sound_type) = sound_type {
-                            self.play_sound_for_everyone(bobber_pos, sound_type);
+                        if splashed {
+                            self.play_sound_for_everyone(bobber_pos, ClientSound::Splash);
                         }
                     }
                     FishingState::PreReeling { fish, bobber_pos } => {
-                        self.player.fishing_state = FishingState::Reeling { fish, bobber_pos };
-                    }
-                    FishingState::Reeling { fish, bobber_pos } => {
-                        self.player.fishing_state = FishingState::Waiting(bobber_pos);
+                        self.player.fishing_state =
+                            FishingState::Struggling(Struggle::new(fish, bobber_pos));
                     }
                     FishingState::Waiting(_) => {
                         ignore = true;
@@ -279,14 +270,17 @@ impl Game {
             }
         }
 
+        self.update_struggle(delta_time);
+
         self.player.fish_in_hands = self
             .hovered_inventory_slot
-            .and_then(|index| self.inventory.get(index).copied());
+            .and_then(|index| self.player.inventory.get(index))
+            .map(|fish| fish.fish_type);
     }
 
     pub fn can_join(&self, seated: &mut bool) -> Option<(Player, usize)> {
         let pos = self.world_pos(self.geng.window().mouse_pos().map(|x| x as f32));
-        let land = |pos| Map::get().get_height(pos) > SHORE_HEIGHT;
+        let land = |pos| self.world.map.get_height(pos) > SHORE_HEIGHT;
         if self.player.seated.is_none() && land(self.player.pos.pos) {
             for other_player in &self.model.get().players {
                 if other_player.id == self.player_id {
@@ -303,7 +297,7 @@ impl Game {
                 let mut other_player_radius = 1.0;
                 if other_player.boat_level > 0 {
                     other_player_radius *=
-                        self.assets.config.boat_types[(other_player.boat_level - 1) as usize].scale;
+                        self.world.config.boat_types[(other_player.boat_level - 1) as usize].scale;
                 }
                 // Make sure we are in range of their boat
                 if (p.get().pos - self.player.pos.pos).len() > (2.5 + other_player_radius / 2.0) {
@@ -2,8 +2,13 @@
 use geng::net::simple as simple_net;
 use geng::prelude::*;
 
+pub mod achievements;
 pub mod assets;
+pub mod bite;
 pub mod camera;
+pub mod catch;
+pub mod daytime;
+pub mod economy;
 pub mod fish;
 pub mod interpolation;
 pub mod inventory;
@@ -13,13 +18,31 @@ pub mod model;
 pub mod movement;
 pub mod obj;
 pub mod player;
+pub mod profiles;
+pub mod quests;
+pub mod reeling;
+pub mod regions;
+pub mod respawn;
+pub mod segments;
 pub mod shops;
+pub mod snapshot;
 pub mod sound;
+pub mod spatial;
 pub mod splash;
+pub mod stocks;
+pub mod sync;
 pub mod util;
+pub mod validation;
+pub mod weather;
+pub mod world;
 
+pub use achievements::*;
 pub use assets::*;
+pub use bite::*;
 pub use camera::*;
+pub use catch::*;
+pub use daytime::*;
+pub use economy::*;
 pub use fish::*;
 pub use interpolation::*;
 pub use inventory::*;
@@ -29,12 +52,26 @@ pub use model::*;
 pub use movement::*;
 pub use obj::*;
 pub use player::*;
+pub use profiles::*;
+pub use quests::*;
+pub use reeling::*;
+pub use regions::*;
+pub use respawn::*;
+pub use segments::*;
 pub use shops::*;
+pub use snapshot::*;
 pub use sound::*;
+pub use spatial::*;
 pub use splash::*;
+pub use stocks::*;
+pub use sync::*;
 pub use util::*;
+pub use validation::*;
+pub use weather::*;
+pub use world::*;
 
 pub const SHOPPING_DISTANCE: f32 = 2.0;
+pub const DEFAULT_PORT: u16 = 1155;
 pub const MUSIC_VOL: f64 = 0.18;
 pub const SPACE_MUSIC_VOL: f64 = 0.28;
 pub const FADE_DIST: f32 = 3.0;
@@ -42,12 +79,15 @@ pub const FADE_DIST: f32 = 3.0;
 pub struct Game {
     player_id: Id,
     model: simple_net::Remote<Model>,
+    world: WorldRef,
     interpolated: HashMap<Id, InterpolatedPosition>,
     framebuffer_size: Vec2<f32>,
     camera: Camera,
     geng: Geng,
     time: f32,
     assets: Rc<Assets>,
+    // Index in the fish assets for every fish type of the world
+    fish_asset_indices: Vec<usize>,
     white_texture: ugli::Texture,
     player: Player,
     player_control: PlayerMovementControl,
@@ -57,10 +97,7 @@ pub struct Game {
     send_ping: bool,
     map_geometry: MapGeometry,
     caught_fish: Collection<CaughtFish>,
-    inventory: Vec<FishType>,
     hovered_inventory_slot: Option<usize>,
-    money: u32,
-    fishdex: HashSet<FishType>,
     splashes: Vec<Splash>,
     players_trail_times: HashMap<Id, f32>,
     boat_sound_effects: HashMap<Id, geng::SoundEffect>,
@@ -75,6 +112,10 @@ pub struct Game {
     show_reel_tutorial: bool,
     music_track: MusicTrack,
     current_music: geng::SoundEffect,
+    fog_density: f32,
+    toasts: Vec<Toast>,
+    // The fish shop shows the fishing reports instead of its goods
+    show_reports: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -98,6 +139,7 @@ impl Game {
         player_id: Id,
         model: simple_net::Remote<Model>,
     ) -> Self {
+        let world = model.get().world.clone();
         let mut land_environment: Vec<ugli::VertexBuffer<ObjInstance>> =
             (0..assets.environment.land.len())
                 .map(|_| ugli::VertexBuffer::new_static(geng.ugli(), vec![]))
@@ -113,10 +155,11 @@ impl Game {
         {
             // Generate the environment
             let mut rng = StdRng::seed_from_u64(1234);
-            let (w, h) = Map::get().get_dimensions();
+            let (w, h) = world.map.get_dimensions();
             for x in 0..w {
                 for y in 0..h {
-                    let pixel_value = Map::get()
+                    let pixel_value = world
+                        .map
                         .get_pixel(Vec2 {
                             x: x as i32,
                             y: y as i32,
@@ -124,10 +167,10 @@ impl Game {
                         .0[0];
                     if pixel_value > 220 {
                         let mut pos = Vec2 {
-                            x: 100.0 * (x as f32 / w as f32 * 2.0 - 1.0),
-                            y: 100.0 * (y as f32 / h as f32 * 2.0 - 1.0),
+                            x: world.map.size * (x as f32 / w as f32 * 2.0 - 1.0),
+                            y: world.map.size * (y as f32 / h as f32 * 2.0 - 1.0),
                         };
-                        if Map::get().get_is_void(pos) || Map::get().is_ice(pos) {
+                        if world.map.get_is_void(pos) || world.map.is_ice(pos) {
                             continue;
                         }
                         let weight = pixel_value as u32 - 220;
@@ -136,7 +179,7 @@ impl Game {
                             continue;
                         }
                         pos.y += rng_choice as f32 * 0.00005;
-                        let height = Map::get().get_height(pos);
+                        let height = world.map.get_height(pos);
                         let mut big_tree = rng.gen_range(0..40);
                         if pos.len() < 20.0 {
                             big_tree = 10;
@@ -164,9 +207,10 @@ impl Game {
                 }
             }
             for _ in 0..10000 {
-                let pos = vec2(rng.gen_range(-SIZE..SIZE), rng.gen_range(-SIZE..SIZE));
-                let height = Map::get().get_height(pos);
-                if Map::get().get_is_void(pos) || Map::get().is_ice(pos) {
+                let size = world.map.size;
+                let pos = vec2(rng.gen_range(-size..size), rng.gen_range(-size..size));
+                let height = world.map.get_height(pos);
+                if world.map.get_is_void(pos) || world.map.is_ice(pos) {
                     continue;
                 }
                 if height > 0.0 {
@@ -194,8 +238,25 @@ impl Game {
                 }
             }
         }
+        // Matched by name, so custom maps can use any of the bundled species
+        let fish_asset_indices = world
+            .fish_configs
+            .configs
+            .iter()
+            .map(|config| {
+                let index = assets
+                    .fishes
+                    .iter()
+                    .position(|fish| fish.config.name == config.name);
+                index.unwrap_or_else(|| {
+                    error!("There is no texture for {:?}", config.name);
+                    0
+                })
+            })
+            .collect();
         Self {
             show_names: true,
+            fish_asset_indices,
             show_reel_tutorial: true,
             target_cam_distance: 20.0,
             editing_name: true,
@@ -204,6 +265,7 @@ impl Game {
             shallow_environment,
             player_id,
             model,
+            world: world.clone(),
             time: 0.0,
             framebuffer_size: vec2(1.0, 1.0),
             geng: geng.clone(),
@@ -243,16 +305,13 @@ impl Game {
                     },
                 ],
             ),
-            map_geometry: create_map_geometry(geng, assets),
+            map_geometry: create_map_geometry(geng, assets, &world.map),
             interpolated: HashMap::new(),
             ping_time: 0.0,
             send_ping: false,
             player_timings: HashMap::new(),
             caught_fish: Collection::new(),
-            inventory: Vec::new(),
             hovered_inventory_slot: None,
-            money: 0,
-            fishdex: HashSet::new(),
             splashes: Vec::new(),
             players_trail_times: HashMap::new(),
             boat_sound_effects: HashMap::new(),
@@ -264,6 +323,9 @@ impl Game {
                 effect.set_volume(MUSIC_VOL);
                 effect
             },
+            fog_density: 0.0,
+            toasts: Vec::new(),
+            show_reports: false,
         }
     }
 
@@ -290,6 +352,8 @@ impl Game {
             (
                 ugli::uniforms! {
                     u_color: color,
+                    u_light: self.light(),
+                    u_fog_density: self.fog_density,
                     u_texture: texture,
                 },
                 geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -338,6 +402,8 @@ impl Game {
                 (
                     ugli::uniforms! {
                         u_color: Rgba::WHITE,
+                        u_light: self.light(),
+                        u_fog_density: self.fog_density,
                         u_texture: &self.assets.environment.trees[index],
                     },
                     geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -358,6 +424,8 @@ impl Game {
                 (
                     ugli::uniforms! {
                         u_color: Rgba::WHITE,
+                        u_light: self.light(),
+                        u_fog_density: self.fog_density,
                         u_texture: &self.assets.environment.land[index],
                     },
                     geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -378,6 +446,8 @@ impl Game {
                 (
                     ugli::uniforms! {
                         u_color: Rgba::WHITE,
+                        u_light: self.light(),
+                        u_fog_density: self.fog_density,
                         u_texture: &self.assets.environment.shallow[index],
                     },
                     geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -413,12 +483,12 @@ impl geng::State for Game {
                             .camera
                             .pos
                             .xy()
-                            .map(|x| (-x / SIZE).clamp(-1.0, 1.0) * 0.2),
+                            .map(|x| (-x / self.world.map.size).clamp(-1.0, 1.0) * 0.2),
                 ),
         );
         ugli::clear(
             framebuffer,
-            None, // Some(self.assets.config.space_color),
+            None, // Some(self.world.config.space_color),
             Some(1.0),
             None,
         );
@@ -430,8 +500,10 @@ impl geng::State for Game {
             &self.map_geometry.land,
             (
                 ugli::uniforms! {
-                    u_heightmap: &self.assets.map,
-                    u_texture: &self.assets.map_color,
+                    u_heightmap: &self.map_geometry.heightmap,
+                    u_texture: &self.map_geometry.colormap,
+                    u_light: self.light(),
+                    u_fog_density: self.fog_density,
                 },
                 geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
             ),
@@ -468,7 +540,7 @@ impl geng::State for Game {
                 &self.map_geometry.land,
                 (
                     ugli::uniforms! {
-                        u_heightmap: &self.assets.map,
+                        u_heightmap: &self.map_geometry.heightmap,
                     },
                     geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
                 ),
@@ -484,7 +556,7 @@ impl geng::State for Game {
                 &self.map_geometry.edge,
                 (
                     ugli::uniforms! {
-                        u_heightmap: &self.assets.map,
+                        u_heightmap: &self.map_geometry.heightmap,
                     },
                     geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
                 ),
@@ -503,13 +575,15 @@ impl geng::State for Game {
             &self.map_geometry.water,
             (
                 ugli::uniforms! {
-                    u_heightmap: &self.assets.map,
+                    u_heightmap: &self.map_geometry.heightmap,
                     surfaceNoise: &self.assets.surface_noise,
                     distortNoise: &self.assets.distort_noise,
                     u_depth_texture: &depth_texture,
                     u_framebuffer_size: self.framebuffer_size,
                     u_model_matrix: Mat4::identity(),
                     u_time: self.time,
+                    u_light: self.light(),
+                    u_fog_density: self.fog_density,
                 },
                 geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
             ),
@@ -527,6 +601,8 @@ impl geng::State for Game {
             (
                 ugli::uniforms! {
                     u_time: self.time,
+                    u_light: self.light(),
+                    u_fog_density: self.fog_density,
                     surfaceNoise: &self.assets.surface_noise,
                 },
                 geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -540,6 +616,8 @@ impl geng::State for Game {
 
         self.draw_splashes(framebuffer);
         self.draw_inventory(framebuffer);
+        self.draw_tension(framebuffer);
+        self.draw_toasts(framebuffer);
     }
 
     fn update(&mut self, delta_time: f64) {
@@ -581,7 +659,16 @@ impl geng::State for Game {
                 (self.target_cam_distance - self.camera.distance).clamp_abs(delta_time * 30.0);
         }
 
-        self.player.inventory = self.inventory.clone(); // NOICE
+        if let Some(player) = self.model.get().players.get(&self.player_id) {
+            self.player.money = player.money;
+            self.player.boat_level = player.boat_level;
+            self.player.inventory = player.inventory.clone();
+            self.player.fishdex = player.fishdex.clone();
+            self.player.baits = player.baits.clone();
+            self.player.rod_level = player.rod_level;
+            self.player.quests = player.quests.clone();
+            self.player.achievements = player.achievements.clone();
+        }
 
         self.geng
             .audio()
@@ -628,26 +715,141 @@ impl geng::State for Game {
                 Event::CaughtFish {
                     player,
                     fish,
-                    fish_type,
+                    caught,
                     position,
                 } => {
                     self.caught_fish.insert(CaughtFish {
                         id: fish,
-                        index: fish_type,
+                        index: caught.fish_type,
                         player,
                         lifetime: 0.0,
                         caught_at: position,
                     });
+                    if player == self.player_id && caught.rarity != Rarity::Common {
+                        let config = &self.world.fish_configs.configs[caught.fish_type];
+                        self.tutorial = format!("you caught {}!", caught.description(config));
+                        self.tutorial_timer = 3.0;
+                    }
+                }
+                Event::CatchRejected { fish } => {
+                    // Undo the flying fish
+                    self.caught_fish.remove(&fish);
                 }
                 Event::Sound {
                     player,
                     sound_type,
                     pos,
                 } => {
-                    if player != self.player_id {
+                    if player != Some(self.player_id) {
                         self.play_sound(pos, sound_type);
                     }
                 }
+                Event::SoldFish {
+                    player,
+                    fish,
+                    price,
+                } => {
+                    if player == self.player_id {
+                        let config = &self.world.fish_configs.configs[fish.fish_type];
+                        self.tutorial = format!("sold {} for ${}", fish.description(config), price);
+                        self.tutorial_timer = 2.0;
+                    }
+                }
+                Event::BoughtBoat { player, level } => {
+                    if player == self.player_id {
+                        self.player.boat_level = level;
+                        if level == 1 {
+                            self.tutorial =
+                                "right click water when near it\nto get into the boat".to_owned();
+                            self.tutorial_timer = 10.0;
+                        }
+                        if level == 2 {
+                            self.tutorial = "you can now explore the deep sea".to_owned();
+                            self.tutorial_timer = 10.0;
+                        }
+                        if level == 3 {
+                            self.tutorial =
+                                "you can now explore beyond the edge of the world".to_owned();
+                            self.tutorial_timer = 10.0;
+                        }
+                    }
+                }
+                Event::BoughtBait { player, bait } => {
+                    if player == self.player_id {
+                        self.player.selected_bait = Some(bait);
+                        self.tutorial = "press B to switch bait".to_owned();
+                        self.tutorial_timer = 3.0;
+                    }
+                }
+                Event::BoughtRod { player, level } => {
+                    if player == self.player_id {
+                        self.player.rod_level = level;
+                        let rod = self.world.config.rod(level);
+                        self.tutorial = format!("you can now cast {} meters", rod.line_length);
+                        self.tutorial_timer = 5.0;
+                    }
+                }
+                Event::AcceptedBounty { player, .. } => {
+                    if player == self.player_id {
+                        self.tutorial = "bounty taken, come back when you are done".to_owned();
+                        self.tutorial_timer = 3.0;
+                    }
+                }
+                Event::QuestCompleted { player, .. } => {
+                    if player == self.player_id {
+                        self.tutorial = "bounty done, return to the shop to claim it".to_owned();
+                        self.tutorial_timer = 3.0;
+                    }
+                }
+                Event::ClaimedBounty { player, reward } => {
+                    if player == self.player_id {
+                        self.tutorial = format!("got ${reward} for the bounty");
+                        self.tutorial_timer = 3.0;
+                    }
+                }
+                Event::AchievementUnlocked {
+                    player,
+                    achievement,
+                } => {
+                    self.show_achievement(player, achievement);
+                }
+                Event::WeatherChanged(weather) => {
+                    self.tutorial = weather.forecast().to_owned();
+                    self.tutorial_timer = 5.0;
+                }
+                Event::WashedOverboard { player, fish } => {
+                    if player == self.player_id {
+                        let config = &self.world.fish_configs.configs[fish.fish_type];
+                        self.tutorial =
+                            format!("a wave washed your {} overboard!", fish.description(config));
+                        self.tutorial_timer = 3.0;
+                        self.splashes.push(Splash::new(self.player.pos.pos, 5, 1.0));
+                    }
+                }
+                Event::InventoryFull { .. } => {
+                    self.tutorial =
+                        "your inventory is limited!\nyou should maybe go sell some fish?"
+                            .to_owned();
+                    self.tutorial_timer = 5.0;
+                }
+                Event::TransactionFailed(error) => {
+                    self.tutorial = error.message().to_owned();
+                    self.tutorial_timer = 3.0;
+                }
+                Event::ProfileRestored(player) => {
+                    self.editing_name = false;
+                    self.player.name = player.name;
+                    self.player.colors = player.colors;
+                }
+                Event::Correction(player) => {
+                    if player.fishing_state != self.player.fishing_state {
+                        self.player_timings.remove(&self.player_id);
+                    }
+                    if player.pos != self.player.pos {
+                        self.player_control = PlayerMovementControl::GoDirection(Vec2::ZERO);
+                    }
+                    self.player = player;
+                }
             }
         }
         self.ping_time += delta_time;
@@ -660,11 +862,13 @@ impl geng::State for Game {
         self.time += delta_time;
 
         self.update_my_player(delta_time);
+        self.update_weather(delta_time);
+        self.update_toasts(delta_time);
         self.update_local_player_data(delta_time);
 
         let model = self.model.get();
         for player in &model.players {
-            if Map::get().get_height(player.pos.pos) < 0.0
+            if self.world.map.get_height(player.pos.pos) < 0.0
                 && player.seated.is_none()
                 && player.pos.vel.len() > 1.0
             {
@@ -680,31 +884,9 @@ impl geng::State for Game {
 
         for fish in &mut self.caught_fish {
             fish.lifetime += delta_time;
-            if fish.lifetime >= 1.0 && fish.player == self.player_id {
-                self.fishdex.insert(fish.index);
-                self.inventory.push(fish.index);
-            }
         }
         self.caught_fish.retain(|fish| fish.lifetime < 1.0);
 
-        if self.inventory.len() > self.assets.config.inventory_size {
-            self.tutorial =
-                "your inventory is limited!\nyou should maybe go sell some fish?".to_owned();
-            self.tutorial_timer = 5.0;
-            self.model.send(Message::SpawnFish {
-                index: self.inventory.remove(0),
-                pos: self.player.pos.pos,
-            });
-            self.play_sound_for_everyone(
-                self.player.pos.pos,
-                if Map::get().get_height(self.player.pos.pos) > 0.0 {
-                    SoundType::DropFishLand
-                } else {
-                    SoundType::DropFishWater
-                },
-            );
-        }
-
         for splash in &mut self.splashes {
             splash.lifetime += delta_time * splash.speed;
         }
@@ -720,74 +902,47 @@ impl geng::State for Game {
                         let mut can_fish = true;
                         if let Some(index) = self.hovered_inventory_slot {
                             can_fish = false;
-                            if index < self.inventory.len() {
-                                let fish = self.inventory.remove(index);
+                            if index < self.player.inventory.len() {
+                                // Make sure the server knows where we are standing
+                                self.model.send(Message::Update(self.player.clone()));
                                 if self.can_sell_fish() {
-                                    self.money += self.assets.fishes[fish].config.cost;
-                                    self.play_sound_for_everyone(
-                                        self.player.pos.pos,
-                                        SoundType::SellFish,
-                                    );
-                                    self.model.send(Message::RespawnFish { index: fish });
+                                    self.model.send(Message::SellFish { slot: index });
                                 } else {
-                                    self.play_sound_for_everyone(
+                                    // The server tells everyone else
+                                    self.play_sound(
                                         self.player.pos.pos,
-                                        if Map::get().get_height(self.player.pos.pos) > 0.0 {
+                                        if self.world.map.get_height(self.player.pos.pos) > 0.0 {
                                             SoundType::DropFishLand
                                         } else {
                                             SoundType::DropFishWater
                                         },
                                     );
                                     self.model.send(Message::SpawnFish {
-                                        index: fish,
+                                        slot: index,
                                         pos: self.player.pos.pos,
                                     });
                                 }
                             }
                         }
-                        if let Some((index, boat_type)) = self.is_hovering_boat_shop() {
-                            let boat_level = index as u8 + 1;
+                        if let Some((index, _)) = self.is_hovering_boat_shop() {
                             can_fish = false;
-                            if self.money >= boat_type.cost {
-                                self.money -= boat_type.cost;
-                                self.player.boat_level = boat_level;
-                                self.play_sound_for_everyone(
-                                    self.player.pos.pos,
-                                    SoundType::UpgradeBoat,
-                                );
-                                if boat_level == 1 {
-                                    self.tutorial =
-                                        "right click water when near it\nto get into the boat"
-                                            .to_owned();
-                                    self.tutorial_timer = 10.0;
-                                }
-                                if boat_level == 2 {
-                                    self.tutorial = "you can now explore the deep sea".to_owned();
-                                    self.tutorial_timer = 10.0;
-                                }
-                                if boat_level == 3 {
-                                    self.tutorial =
-                                        "you can now explore beyond the edge of the world"
-                                            .to_owned();
-                                    self.tutorial_timer = 10.0;
-                                }
-                            }
+                            self.model.send(Message::Update(self.player.clone()));
+                            self.model.send(Message::BuyBoat {
+                                level: index as u8 + 1,
+                            });
                         }
                         if can_fish {
                             match self.player.fishing_state {
                                 FishingState::Idle => {
                                     self.player.fishing_state = FishingState::Spinning;
                                 }
-                                FishingState::Reeling { fish, .. } => {
-                                    self.model.send(Message::Catch(fish));
-                                    self.player.fishing_state = FishingState::Idle;
-                                    self.play_sound(self.player.pos.pos, SoundType::Ding);
-                                }
+                                // Reeling is done by holding the button
+                                FishingState::PreReeling { .. } | FishingState::Struggling(_) => {}
                                 _ => {
                                     self.player.fishing_state = FishingState::Idle;
                                     self.play_sound_for_everyone(
                                         self.player.pos.pos,
-                                        SoundType::StopFishing,
+                                        ClientSound::StopFishing,
                                     );
                                 }
                             }
@@ -795,7 +950,8 @@ impl geng::State for Game {
                     }
                     geng::MouseButton::Right => {
                         let mut teleport = None;
-                        let land = |pos| Map::get().get_height(pos) > SHORE_HEIGHT;
+                        let world = self.world.clone();
+                        let land = |pos| world.map.get_height(pos) > SHORE_HEIGHT;
                         let mut seated = false;
                         if let Some((other_player, seat)) = self.can_join(&mut seated) {
                             // let other_player = other_player.clone();
@@ -805,7 +961,7 @@ impl geng::State for Game {
                             });
                             self.play_sound_for_everyone(
                                 other_player.pos.pos,
-                                SoundType::EnterBoat,
+                                ClientSound::EnterBoat,
                             );
                         }
                         let raycast = |to, from| {
@@ -852,7 +1008,7 @@ impl geng::State for Game {
                         {
                             // teleport between land <> water (our own boat)
                             let mut player_radius = 1.0;
-                            player_radius *= self.assets.config.boat_types
+                            player_radius *= self.world.config.boat_types
                                 [(self.player.boat_level - 1) as usize]
                                 .scale;
 
@@ -867,9 +1023,9 @@ impl geng::State for Game {
                             self.play_sound_for_everyone(
                                 pos,
                                 if land(pos) {
-                                    SoundType::ExitBoat
+                                    ClientSound::ExitBoat
                                 } else {
-                                    SoundType::EnterBoat
+                                    ClientSound::EnterBoat
                                 },
                             );
                             self.player.pos.pos = pos;
@@ -888,12 +1044,14 @@ impl geng::State for Game {
                 match button {
                     geng::MouseButton::Left => {
                         if let FishingState::Spinning = self.player.fishing_state {
+                            let rod = self.world.config.rod(self.player.rod_level);
                             self.player.fishing_state = FishingState::Casting(
                                 self.player.pos.pos
-                                    + (pos - self.player.pos.pos).clamp_len(..=MAX_LINE_LEN - 0.1),
+                                    + (pos - self.player.pos.pos)
+                                        .clamp_len(..=rod.line_length - 0.1),
                             );
-                            self.play_sound_for_everyone(self.player.pos.pos, SoundType::Casting);
-                            self.play_sound_for_everyone(self.player.pos.pos, SoundType::Whip);
+                            self.play_sound_for_everyone(self.player.pos.pos, ClientSound::Casting);
+                            self.play_sound_for_everyone(self.player.pos.pos, ClientSound::Whip);
                         }
                     }
                     geng::MouseButton::Right => {
@@ -928,6 +1086,9 @@ impl geng::State for Game {
                 if key == geng::Key::Tab {
                     self.show_names = !self.show_names;
                 }
+                if !self.editing_name {
+                    self.shop_key(key);
+                }
                 if key == geng::Key::PageDown {
                     self.player.pos.pos = Vec2::ZERO;
                     self.player.seated = None;
@@ -937,7 +1098,7 @@ impl geng::State for Game {
                     if key == geng::Key::Backspace {
                         self.player.name.pop();
                     }
-                    if self.player.name.len() < 15 {
+                    if self.player.name.len() < MAX_NAME_LEN {
                         let s = format!("{key:?}");
                         if s.len() == 1 {
                             self.player.name.push_str(&s);
@@ -950,28 +1111,136 @@ impl geng::State for Game {
     }
 }
 
+// Identifies this client across sessions so the server can restore the profile
+fn client_token() -> String {
+    const KEY: &str = "sea-of-friends-token";
+    if let Some(token) = preferences::load(KEY) {
+        return token;
+    }
+    let token = format!(
+        "{:016x}{:016x}",
+        global_rng().gen::<u64>(),
+        global_rng().gen::<u64>()
+    );
+    preferences::save(KEY, &token);
+    token
+}
+
+#[derive(clap::Parser)]
+struct Opt {
+    /// Start the dedicated server from a world snapshot instead of a fresh world
+    #[clap(long)]
+    resume: Option<std::path::PathBuf>,
+    /// Periodically save the world of the dedicated server to this file
+    #[clap(long)]
+    autosave: Option<std::path::PathBuf>,
+    /// Run only the server, without a window
+    #[clap(long)]
+    server_only: bool,
+    /// Address the dedicated server listens on
+    #[clap(long, default_value = "0.0.0.0")]
+    bind: String,
+    #[clap(long, default_value_t = DEFAULT_PORT)]
+    port: u16,
+    /// Simulation steps per second on the dedicated server, from 1 to 120.
+    /// Only splits the work between network ticks, clients are updated at the same rate
+    #[clap(long, value_parser = parse_tick_rate)]
+    tick_rate: Option<f32>,
+    /// Manifest of the map to host instead of the bundled one,
+    /// clients need a copy of it in `static/maps/<name>/map.json`
+    #[clap(long)]
+    map: Option<std::path::PathBuf>,
+    #[clap(long)]
+    server: Option<String>,
+    #[clap(long)]
+    connect: Option<String>,
+}
+
+const MAX_TICK_RATE: f32 = 120.0;
+
+fn parse_tick_rate(arg: &str) -> Result<f32, String> {
+    let tick_rate: f32 = arg.parse().map_err(|e| format!("{e}"))?;
+    if !(1.0..=MAX_TICK_RATE).contains(&tick_rate) {
+        return Err(format!("must be between 1 and {MAX_TICK_RATE}"));
+    }
+    Ok(tick_rate)
+}
+
+fn or_exit<T>(result: anyhow::Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        error!("{e:#}");
+        std::process::exit(1);
+    })
+}
+
+// The server started together with the game keeps its profiles in the working directory
+fn local_server_model() -> Model {
+    let mut model = Model::init();
+    *model.profiles = or_exit(ProfileStore::open(PROFILES_PATH));
+    model
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run_dedicated_server(opt: Opt) {
+    let world = match &opt.map {
+        Some(path) => WorldRef::register(or_exit(World::load(path))),
+        None => WorldRef::default_world(),
+    };
+    info!("Hosting world {:?}", world.name);
+    let mut model = match &opt.resume {
+        Some(path) => or_exit(Model::resume(world, or_exit(WorldSnapshot::load(path)))),
+        None => Model::new(world),
+    };
+    *model.profiles = or_exit(ProfileStore::open(PROFILES_PATH));
+    *model.autosave_path = opt.autosave;
+    if let Some(tick_rate) = opt.tick_rate {
+        *model.tick_rate = tick_rate;
+    }
+    let addr = opt
+        .server
+        .unwrap_or_else(|| format!("{}:{}", opt.bind, opt.port));
+    info!("Starting the server on {addr}");
+    simple_net::Server::new(addr.as_str(), model).run();
+}
+
 fn main() {
     logger::init().unwrap();
     geng::setup_panic_handler();
+    let opt: Opt = program_args::parse();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if opt.server_only || (opt.server.is_some() && opt.connect.is_none()) {
+        run_dedicated_server(opt);
+        return;
+    }
+
     // let geng = Geng::new("Sea of Friends");
     simple_net::run(
         "Sea of Friends",
-        Model::init,
+        local_server_model,
         move |geng, player_id, model| {
-            geng::LoadingScreen::new(
-                geng,
-                geng::EmptyLoadingScreen,
-                geng::LoadAsset::load(geng, &static_path().join("assets")),
-                {
-                    let geng = geng.clone();
-                    move |assets| {
-                        let assets = assets.unwrap();
-                        let assets = Rc::new(assets);
-                        model.send(Message::Ping);
-                        Game::new(&geng, &assets, player_id, model)
-                    }
-                },
-            )
+            // Maps other than the bundled one are loaded along with the assets
+            let world = model.get().world.clone();
+            let assets = <Assets as geng::LoadAsset>::load(geng, &static_path().join("assets"));
+            let loading = {
+                let geng = geng.clone();
+                async move {
+                    world.load(&geng).await?;
+                    assets.await
+                }
+            };
+            geng::LoadingScreen::new(geng, geng::EmptyLoadingScreen, loading, {
+                let geng = geng.clone();
+                move |assets| {
+                    let assets = assets.unwrap();
+                    let assets = Rc::new(assets);
+                    model.send(Message::Login {
+                        token: client_token(),
+                    });
+                    model.send(Message::Ping);
+                    Game::new(&geng, &assets, player_id, model)
+                }
+            })
         },
     );
 }
@@ -30,69 +30,129 @@ impl Default for IdGen {
 pub struct FishConfigs {
     pub configs: Vec<FishConfig>,
 }
-static mut FISH_CONFIG: Option<FishConfigs> = None;
-
-impl FishConfigs {
-    pub fn get() -> &'static FishConfigs {
-        unsafe { FISH_CONFIG.get_or_insert_with(FishConfigs::load) }
-    }
-    pub fn load() -> Self {
-        Self {
-            configs: serde_json::from_reader(
-                std::fs::File::open(static_path().join("assets").join("fish").join("list.json"))
-                    .unwrap(),
-            )
-            .unwrap(),
-        }
-    }
-}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Diff)]
 pub struct Model {
+    #[diff = "eq"]
+    pub world: WorldRef,
     #[diff = "clone"]
     pub id_gen: IdGen,
-    #[diff = "clone"]
-    pub players: Collection<Player>,
-    #[diff = "clone"]
-    pub fishes: Collection<Fish>,
+    pub players: SyncCollection<Player>,
+    pub fishes: SyncCollection<Fish>,
     pub time: f32,
+    pub move_anchors: ServerOnly<HashMap<Id, MoveAnchor>>,
+    pub struggle_checks: ServerOnly<HashMap<Id, StruggleCheck>>,
+    pub client_event_limits: ServerOnly<HashMap<Id, RateLimit>>,
+    pub tokens: ServerOnly<HashMap<Id, String>>,
+    pub profiles: ServerOnly<ProfileStore>,
+    pub autosave_path: ServerOnly<Option<std::path::PathBuf>>,
+    // Independent from how often the clients are updated
+    pub tick_rate: ServerOnly<f32>,
+    pub unsimulated_time: ServerOnly<f32>,
+    pub rng: ServerOnly<ModelRng>,
+    // When the bobbers of the waiting players landed
+    pub bobber_times: ServerOnly<HashMap<Id, f32>>,
+    #[diff = "eq"]
+    pub weather: Weather,
+    pub weather_until: ServerOnly<f32>,
+    #[diff = "eq"]
+    pub bounties: Vec<Bounty>,
+    // When the next missing fish of each species comes back
+    pub respawn_times: ServerOnly<HashMap<FishType, f32>>,
+    #[diff = "eq"]
+    pub stocks: FishStocks,
 }
 
 impl Model {
-    pub fn init() -> Self {
-        let id_gen = IdGen::new();
-        let mut result = Self {
-            players: Collection::new(),
-            fishes: Collection::new(),
-            id_gen,
+    pub fn empty(world: WorldRef) -> Self {
+        let weather_until = world.config.weather.min_duration;
+        let stocks = FishStocks::new(&world);
+        Self {
+            world,
+            players: SyncCollection::new(),
+            fishes: SyncCollection::new(),
+            id_gen: IdGen::new(),
             time: 0.0,
-        };
-        for i in 0..FishConfigs::get().configs.len() {
+            move_anchors: default(),
+            struggle_checks: default(),
+            client_event_limits: default(),
+            tokens: default(),
+            profiles: default(),
+            autosave_path: default(),
+            tick_rate: ServerOnly(<Self as simple_net::Model>::TICKS_PER_SECOND),
+            unsimulated_time: default(),
+            rng: default(),
+            bobber_times: default(),
+            weather: Weather::Clear,
+            weather_until: ServerOnly(weather_until),
+            bounties: Vec::new(),
+            respawn_times: default(),
+            stocks,
+        }
+    }
+
+    pub fn new(world: WorldRef) -> Self {
+        Self::with_rng(world, ModelRng::default())
+    }
+
+    // A world that plays out the same way every time, for tests
+    pub fn seeded(world: WorldRef, seed: u64) -> Self {
+        Self::with_rng(world, ModelRng::seeded(seed))
+    }
+
+    fn with_rng(world: WorldRef, rng: ModelRng) -> Self {
+        let mut result = Self::empty(world);
+        result.rng = ServerOnly(rng);
+        for i in 0..result.world.fish_configs.configs.len() {
             result.spawn_fish_group(i);
         }
+        result.post_bounties();
         result
     }
 
+    pub fn init() -> Self {
+        Self::new(WorldRef::default_world())
+    }
+
+    // Whether the last tick crossed a multiple of the interval
+    pub fn every(&self, interval: f32, delta_time: f32) -> bool {
+        (self.time / interval).floor() != ((self.time - delta_time) / interval).floor()
+    }
+
     pub fn spawn_fish(&mut self, i: usize) {
-        let mut inner_radius: f32 = 0.0;
-        let fish_config = &FishConfigs::get().configs[i];
-        let radius = fish_config.spawn_circle.radius;
-        let center = fish_config.spawn_circle.center;
-        if let Some(r) = fish_config.spawn_circle.inner_radius {
-            inner_radius = r;
+        let spawn_circle = &self.world.fish_configs.configs[i].spawn_circle;
+        let rng: &mut StdRng = &mut self.rng;
+        let mut pos = spawn_circle.center;
+        for _ in 0..SPAWN_TRIES {
+            pos = spawn_circle.sample(&self.world.map, rng);
+            // Fished out areas get fewer of the new fish
+            if rng.gen_bool(self.stocks.get(&self.world, pos) as f64) {
+                break;
+            }
         }
-        // polar coordinates because we're fancy
-        let r = global_rng().gen_range(inner_radius..radius);
-        let angle = global_rng().gen_range(0.0..(f32::PI * 2.0));
-        self.fishes.insert(Fish::new(
-            self.id_gen.gen(),
-            i,
-            vec2(center.x + r * angle.cos(), center.y + r * angle.sin()),
-        ))
+        self.fishes.insert(Fish::new(self.id_gen.gen(), i, pos))
+    }
+
+    pub fn population(&self, index: usize) -> usize {
+        self.fishes
+            .iter()
+            .filter(|fish| fish.index == index)
+            .count()
+    }
+
+    // Whether the species is below its configured count
+    pub fn has_room_for(&self, index: usize) -> bool {
+        self.world
+            .fish_configs
+            .configs
+            .get(index)
+            .map_or(false, |config| {
+                self.population(index) < config.count as usize
+            })
     }
 
     pub fn spawn_fish_group(&mut self, i: usize) {
-        let fish_config = &FishConfigs::get().configs[i];
+        let fish_config = &self.world.fish_configs.configs[i];
         for j in 0..fish_config.count {
             self.spawn_fish(i);
         }
@@ -102,11 +162,17 @@ impl Model {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Message {
     Ping,
+    Login { token: String },
     Update(Player),
     Catch(Id),
-    SpawnFish { index: usize, pos: Vec2<f32> },
-    Broadcast(Event),
-    RespawnFish { index: usize },
+    SpawnFish { slot: usize, pos: Vec2<f32> },
+    Emit(ClientEvent),
+    SellFish { slot: usize },
+    BuyBoat { level: u8 },
+    BuyBait { bait: usize },
+    BuyRod { level: u8 },
+    AcceptBounty { bounty: Id },
+    ClaimBounty { bounty: Id },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -119,14 +185,62 @@ pub enum Event {
     CaughtFish {
         player: Id,
         fish: Id,
-        fish_type: FishType,
+        caught: CaughtFishInstance,
         position: Vec2<f32>,
     },
+    CatchRejected {
+        fish: Id,
+    },
     Sound {
-        player: Id,
+        // Who already played it, everyone else still has to
+        player: Option<Id>,
         sound_type: SoundType,
         pos: Vec2<f32>,
     },
+    SoldFish {
+        player: Id,
+        fish: CaughtFishInstance,
+        price: u32,
+    },
+    BoughtBoat {
+        player: Id,
+        level: u8,
+    },
+    BoughtBait {
+        player: Id,
+        bait: usize,
+    },
+    BoughtRod {
+        player: Id,
+        level: u8,
+    },
+    InventoryFull {
+        dropped: CaughtFishInstance,
+    },
+    AcceptedBounty {
+        player: Id,
+        bounty: Id,
+    },
+    QuestCompleted {
+        player: Id,
+        bounty: Id,
+    },
+    ClaimedBounty {
+        player: Id,
+        reward: u32,
+    },
+    AchievementUnlocked {
+        player: Id,
+        achievement: Achievement,
+    },
+    WeatherChanged(Weather),
+    WashedOverboard {
+        player: Id,
+        fish: CaughtFishInstance,
+    },
+    TransactionFailed(TransactionError),
+    Correction(Player),
+    ProfileRestored(Player),
 }
 
 impl simple_net::Model for Model {
@@ -141,12 +255,15 @@ impl simple_net::Model for Model {
     }
 
     fn drop_player(&mut self, events: &mut Vec<Self::Event>, player_id: &Self::PlayerId) {
-        if let Some(p) = self.players.get(player_id) {
-            for fish in p.inventory.clone() {
-                self.spawn_fish(fish);
-            }
+        if self.tokens.contains_key(player_id) {
+            // The fish stay in the profile until the player comes back
+            self.save_profiles();
         }
         self.players.remove(player_id);
+        self.move_anchors.remove(player_id);
+        self.struggle_checks.remove(player_id);
+        self.client_event_limits.remove(player_id);
+        self.tokens.remove(player_id);
     }
 
     fn handle_message(
@@ -154,40 +271,131 @@ impl simple_net::Model for Model {
         events: &mut Vec<Self::Event>,
         player_id: &Self::PlayerId,
         message: Self::Message,
+    ) -> Vec<Event> {
+        let first_event = events.len();
+        let reply = self.handle_player_message(events, player_id, message);
+        self.award_achievements(first_event, events);
+        reply
+    }
+
+    fn tick(&mut self, events: &mut Vec<Self::Event>) {
+        *self.unsimulated_time += 1.0 / Self::TICKS_PER_SECOND;
+        let delta_time = 1.0 / *self.tick_rate;
+        // Small tolerance so that equal rates always step exactly once
+        while *self.unsimulated_time > delta_time * 0.999 {
+            *self.unsimulated_time -= delta_time;
+            self.step(delta_time, events);
+        }
+    }
+}
+
+impl Model {
+    // Returns the events only meant for the sender
+    fn handle_player_message(
+        &mut self,
+        events: &mut Vec<Event>,
+        player_id: &Id,
+        message: Message,
     ) -> Vec<Event> {
         match message {
             Message::Ping => return vec![Event::Pong],
+            Message::Login { token } => {
+                if let Some(player) = self.login(*player_id, token) {
+                    return vec![Event::ProfileRestored(player)];
+                }
+            }
             Message::Update(data) => {
-                if data.id == *player_id {
-                    *self.players.get_mut(player_id).unwrap() = data;
+                if data.id == *player_id && !self.accept_update(*player_id, data) {
+                    return vec![Event::Correction(
+                        self.players.get(player_id).unwrap().clone(),
+                    )];
                 }
             }
             Message::Catch(id) => {
+                if !self.validate_catch(*player_id, id) {
+                    return vec![Event::CatchRejected { fish: id }];
+                }
+                self.struggle_checks.remove(player_id);
+                if let Some(player) = self.players.get_mut(player_id) {
+                    if !matches!(player.fishing_state, FishingState::Casting(_)) {
+                        player.fishing_state = FishingState::Idle;
+                    }
+                }
                 if let Some(fish) = self.fishes.remove(&id) {
+                    let config = &self.world.fish_configs.configs[fish.index];
+                    let rng: &mut StdRng = &mut self.rng;
+                    let caught = CaughtFishInstance::roll(fish.index, config, rng);
+                    self.stocks.deplete(&self.world, fish.pos.pos);
+                    self.track_quests(*player_id, &caught, events);
                     events.push(Event::CaughtFish {
                         fish: id,
-                        fish_type: fish.index,
+                        caught,
                         player: *player_id,
                         position: fish.pos.pos,
                     });
+                    if let Some(dropped) = self.give_fish(*player_id, caught, events) {
+                        return vec![Event::InventoryFull { dropped }];
+                    }
+                }
+            }
+            Message::SpawnFish { slot, pos } => {
+                if let Err(error) = self.release_fish(*player_id, slot, pos, events) {
+                    return vec![Event::TransactionFailed(error)];
+                }
+            }
+            Message::Emit(event) => {
+                if let Some(event) = self.accept_client_event(*player_id, event) {
+                    events.push(event);
+                }
+            }
+            Message::SellFish { slot } => {
+                if let Err(error) = self.sell_fish(*player_id, slot, events) {
+                    return vec![Event::TransactionFailed(error)];
                 }
             }
-            Message::SpawnFish { index, pos } => {
-                self.fishes.insert(Fish::new(self.id_gen.gen(), index, pos));
+            Message::BuyBoat { level } => {
+                if let Err(error) = self.buy_boat(*player_id, level, events) {
+                    return vec![Event::TransactionFailed(error)];
+                }
+            }
+            Message::BuyBait { bait } => {
+                if let Err(error) = self.buy_bait(*player_id, bait, events) {
+                    return vec![Event::TransactionFailed(error)];
+                }
+            }
+            Message::BuyRod { level } => {
+                if let Err(error) = self.buy_rod(*player_id, level, events) {
+                    return vec![Event::TransactionFailed(error)];
+                }
             }
-            Message::Broadcast(event) => {
-                events.push(event);
+            Message::AcceptBounty { bounty } => {
+                if let Err(error) = self.accept_bounty(*player_id, bounty, events) {
+                    return vec![Event::TransactionFailed(error)];
+                }
             }
-            Message::RespawnFish { index } => {
-                self.spawn_fish(index);
+            Message::ClaimBounty { bounty } => {
+                if let Err(error) = self.claim_bounty(*player_id, bounty, events) {
+                    return vec![Event::TransactionFailed(error)];
+                }
             }
         }
         vec![]
     }
 
-    fn tick(&mut self, events: &mut Vec<Self::Event>) {
-        let delta_time = 1.0 / Self::TICKS_PER_SECOND;
+    fn step(&mut self, delta_time: f32, events: &mut Vec<Event>) {
         self.time += delta_time;
+        if self.every(PROFILE_SAVE_INTERVAL, delta_time) {
+            self.save_profiles();
+        }
+        if self.every(AUTOSAVE_INTERVAL, delta_time) {
+            self.autosave();
+        }
+        self.update_weather(delta_time, events);
+        self.update_bounties(delta_time);
+        self.update_stocks(delta_time);
+        self.update_respawns();
+        self.update_bobber_times();
         self.update_fishes(delta_time, events);
+        self.update_achievements(events);
     }
 }
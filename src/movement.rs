@@ -8,6 +8,57 @@ pub struct Position {
     pub w: f32,
 }
 
+const POSITION_PRECISION: f32 = 64.0;
+const VELOCITY_PRECISION: f32 = 256.0;
+
+fn quantize(value: f32, precision: f32) -> i32 {
+    (value * precision).round() as i32
+}
+
+// Positions change every tick, so their deltas are packed into fixed point,
+// snapshots and full states keep the exact values
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuantizedPosition {
+    pos: Vec2<i32>,
+    vel: Vec2<i32>,
+    rot: f32,
+    w: f32,
+}
+
+impl From<Position> for QuantizedPosition {
+    fn from(position: Position) -> Self {
+        Self {
+            pos: position.pos.map(|x| quantize(x, POSITION_PRECISION)),
+            vel: position.vel.map(|x| quantize(x, VELOCITY_PRECISION)),
+            rot: position.rot,
+            w: position.w,
+        }
+    }
+}
+
+impl From<QuantizedPosition> for Position {
+    fn from(position: QuantizedPosition) -> Self {
+        Self {
+            pos: position.pos.map(|x| x as f32 / POSITION_PRECISION),
+            vel: position.vel.map(|x| x as f32 / VELOCITY_PRECISION),
+            rot: position.rot,
+            w: position.w,
+        }
+    }
+}
+
+impl Diff for Position {
+    type Delta = QuantizedPosition;
+
+    fn diff(&self, to: &Self) -> QuantizedPosition {
+        to.clone().into()
+    }
+
+    fn update(&mut self, delta: &QuantizedPosition) {
+        *self = delta.clone().into();
+    }
+}
+
 #[derive(Clone)]
 pub struct MovementProps {
     pub max_speed: f32,
@@ -45,3 +96,45 @@ pub fn update_movement(
     pos.vel += (target_vel - pos.vel).clamp_len(..=props.acceleration * delta_time);
     pos.pos += pos.vel * delta_time;
 }
+
+impl MovementProps {
+    pub fn player(in_water: bool) -> Self {
+        if in_water {
+            Self {
+                max_speed: 2.0,
+                max_rotation_speed: 2.0,
+                angular_acceleration: 1.0,
+                acceleration: 1.0,
+                water: true,
+            }
+        } else {
+            Self {
+                max_speed: 2.0,
+                max_rotation_speed: 2.0,
+                angular_acceleration: 1.0,
+                acceleration: 10.0,
+                water: false,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_quantized_range() {
+    // Far outside of the bundled map, custom maps can be much bigger
+    let position = Position {
+        pos: vec2(4000.3, -4000.7),
+        vel: vec2(3.0, -2.5),
+        rot: 1.0,
+        w: 0.5,
+    };
+    let mut synced = Position {
+        pos: Vec2::ZERO,
+        vel: Vec2::ZERO,
+        rot: 0.0,
+        w: 0.0,
+    };
+    synced.update(&synced.diff(&position));
+    assert!((synced.pos - position.pos).len() <= 1.0 / POSITION_PRECISION);
+    assert!((synced.vel - position.vel).len() <= 1.0 / VELOCITY_PRECISION);
+}
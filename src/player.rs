@@ -7,16 +7,23 @@ pub enum FishingState {
     Casting(Vec2<f32>),
     Waiting(Vec2<f32>),
     PreReeling { fish: Id, bobber_pos: Vec2<f32> },
-    Reeling { fish: Id, bobber_pos: Vec2<f32> },
+    Struggling(Struggle),
     Attached(Id),
 }
 
 impl FishingState {
     pub fn bobber_pos(&self) -> Option<Vec2<f32>> {
         match self {
-            Self::Waiting(bobber_pos)
-            | Self::PreReeling { bobber_pos, .. }
-            | Self::Reeling { bobber_pos, .. } => Some(*bobber_pos),
+            Self::Waiting(bobber_pos) | Self::PreReeling { bobber_pos, .. } => Some(*bobber_pos),
+            Self::Struggling(struggle) => Some(struggle.bobber_pos),
+            _ => None,
+        }
+    }
+
+    pub fn hooked_fish(&self) -> Option<Id> {
+        match self {
+            Self::PreReeling { fish, .. } => Some(*fish),
+            Self::Struggling(struggle) => Some(struggle.fish),
             _ => None,
         }
     }
@@ -36,17 +43,39 @@ pub struct Seated {
     pub seat: usize,
 }
 
-#[derive(HasId, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(HasId, Debug, Clone, PartialEq, Serialize, Deserialize, Diff)]
 pub struct Player {
+    #[diff = "eq"]
     pub id: Id,
+    #[diff = "eq"]
     pub name: String,
     pub pos: Position,
+    #[diff = "eq"]
     pub fishing_state: FishingState,
+    #[diff = "eq"]
     pub fish_in_hands: Option<FishType>,
+    #[diff = "eq"]
     pub boat_level: u8,
+    #[diff = "eq"]
+    pub rod_level: u8,
+    #[diff = "eq"]
     pub colors: PlayerColors,
+    #[diff = "eq"]
     pub seated: Option<Seated>,
-    pub inventory: Vec<FishType>,
+    #[diff = "eq"]
+    pub inventory: Vec<CaughtFishInstance>,
+    #[diff = "eq"]
+    pub money: u32,
+    #[diff = "eq"]
+    pub fishdex: HashSet<FishType>,
+    #[diff = "eq"]
+    pub baits: Vec<u32>,
+    #[diff = "eq"]
+    pub selected_bait: Option<usize>,
+    #[diff = "eq"]
+    pub quests: Vec<Quest>,
+    #[diff = "eq"]
+    pub achievements: HashSet<Achievement>,
 }
 
 impl Player {
@@ -63,6 +92,7 @@ impl Player {
             fishing_state: FishingState::Idle,
             fish_in_hands: None,
             boat_level: 0,
+            rod_level: 0,
             colors: {
                 let saturation = 0.7;
                 let value = 0.7;
@@ -76,8 +106,20 @@ impl Player {
             },
             seated: None,
             inventory: Vec::new(),
+            money: 0,
+            fishdex: HashSet::new(),
+            baits: Vec::new(),
+            selected_bait: None,
+            quests: Vec::new(),
+            achievements: HashSet::new(),
         }
     }
+
+    // The selected bait, if there is any left
+    pub fn active_bait(&self) -> Option<usize> {
+        self.selected_bait
+            .filter(|&bait| self.baits.get(bait).map_or(false, |&count| count > 0))
+    }
 }
 
 impl Game {
@@ -93,7 +135,6 @@ impl Game {
             let time = match player.fishing_state {
                 FishingState::Casting(_) => Some(1.0),
                 FishingState::PreReeling { .. } => Some(1.0),
-                FishingState::Reeling { .. } => Some(1.0),
                 FishingState::Waiting(_) => Some(1.0),
                 _ => None,
             };
@@ -113,7 +154,7 @@ impl Game {
             let Some(pos) = self.interpolated.get(&player.id) else { continue };
             let pos = pos.get();
             effect.set_position(pos.pos.extend(0.0).map(|x| x as f64));
-            if player.seated.is_some() || Map::get().get_height(pos.pos) > 0.0 {
+            if player.seated.is_some() || self.world.map.get_height(pos.pos) > 0.0 {
                 effect.set_volume(0.0);
             } else {
                 effect.set_volume(pos.vel.len() as f64 / 2.0);
@@ -148,7 +189,7 @@ impl Game {
             * Mat4::rotate_z(
                 (self.camera.eye_pos().xy() - character_pos.xy()).arg() + f32::PI / 2.0,
             );
-        let rot = if Map::get().get_height(character_pos.xy()) > SHORE_HEIGHT {
+        let rot = if self.world.map.get_height(character_pos.xy()) > SHORE_HEIGHT {
             (self.time * 10.0).sin() * player.pos.vel.len().min(1.0) * 0.1
         } else {
             0.0
@@ -234,26 +275,12 @@ impl Game {
                 fishing_rod_rot = Some(0.5);
                 bobber = Some(bobber_pos.extend(0.0));
             }
-            FishingState::Reeling { bobber_pos, .. } => {
-                let t = self
-                    .player_timings
-                    .get(&player.id)
-                    .copied()
-                    .unwrap_or(0.0)
-                    .min(1.0);
-                // Damped oscillation
-                let gamma = 3.0; // damping coefficient
-                let frequency = 2.0 * f32::PI;
-                let amplitude = (-gamma * t).exp() * 0.5;
-                let rot = -amplitude * (frequency * t).cos() + 1.0;
-                fishing_rod_rot = Some(rot);
-
-                // Damped oscillation
-                let gamma = 3.0; // damping coefficient
-                let frequency = 4.0 * f32::PI;
-                let amplitude = (-gamma * t).exp() * 0.2;
-                let bobber_depth = amplitude * (frequency * t).cos() - 0.2;
-                bobber = Some(bobber_pos.extend(bobber_depth));
+            FishingState::Struggling(struggle) => {
+                // The rod bends with the tension and shakes with the fish
+                let shake = (struggle.time * 20.0).sin() * 0.05 * struggle.tension;
+                fishing_rod_rot = Some(0.5 + struggle.tension * 0.7 + shake);
+                let bobber_depth = -0.1 - struggle.tension * 0.2 + shake;
+                bobber = Some(struggle.bobber_pos.extend(bobber_depth));
             }
             FishingState::Attached(id) => {
                 if let Some(player) = self.model.get().players.get(id) {
@@ -266,7 +293,10 @@ impl Game {
         }
         // Draw fishing rod
         if let Some(rot) = fishing_rod_rot {
-            let texture = &self.assets.fishing_rod;
+            let texture = match player.rod_level {
+                0 => &self.assets.fishing_rod,
+                level => &self.assets.rods[(level as usize - 1).min(self.assets.rods.len() - 1)],
+            };
             let mirrored = bobber
                 .map(|bobber| bobber.x < character_pos.x)
                 .unwrap_or(false);
@@ -334,6 +364,8 @@ impl Game {
                     (
                         ugli::uniforms! {
                             u_color: Rgba::WHITE,
+                            u_light: self.light(),
+                            u_fog_density: self.fog_density,
                             u_texture: &self.white_texture,
                         },
                         geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -366,7 +398,7 @@ impl Game {
                 framebuffer,
                 character_pos + vec3(0.0, 0.0, 1.0),
                 0.25,
-                &self.assets.fishes[fish].texture,
+                &self.fish_assets(fish).texture,
                 vec2(0.0, -1.0),
             )
         }
@@ -403,7 +435,7 @@ impl Game {
                 };
                 let model_matrix = Mat4::translate(pos.pos.extend(0.0))
                     * Mat4::rotate_z(pos.rot)
-                    * Mat4::scale_uniform(self.assets.config.boat_types[boat_type_index].scale);
+                    * Mat4::scale_uniform(self.world.config.boat_types[boat_type_index].scale);
                 self.draw_player_character(
                     framebuffer,
                     player,
@@ -412,12 +444,12 @@ impl Game {
             }
             return;
         }
-        let height = Map::get().get_height(pos.pos);
+        let height = self.world.map.get_height(pos.pos);
         if height < SHORE_HEIGHT {
             let boat_type_index = player.boat_level.max(1) as usize - 1;
             let model_matrix = Mat4::translate(pos.pos.extend(0.0))
                 * Mat4::rotate_z(pos.rot)
-                * Mat4::scale_uniform(self.assets.config.boat_types[boat_type_index].scale);
+                * Mat4::scale_uniform(self.world.config.boat_types[boat_type_index].scale);
             let ship = &self.assets.ships[boat_type_index];
             let obj = &ship.obj;
             for mesh in &obj.meshes {
@@ -437,6 +469,8 @@ impl Game {
                     (
                         ugli::uniforms! {
                             u_color: mesh.material.diffuse_color,
+                            u_light: self.light(),
+                            u_fog_density: self.fog_density,
                             u_texture: mesh.material.texture.as_deref().unwrap_or(&self.white_texture),
                         },
                         geng::camera3d_uniforms(&self.camera, self.framebuffer_size),
@@ -0,0 +1,164 @@
+use super::*;
+
+use anyhow::Context as _;
+
+pub const PROFILES_PATH: &str = "profiles.json";
+pub const PROFILE_SAVE_INTERVAL: f32 = 30.0;
+const MAX_TOKEN_LEN: usize = 64;
+
+// Profiles saved before fish had sizes only stored the species
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SavedFish {
+    Caught(CaughtFishInstance),
+    Legacy(FishType),
+}
+
+impl SavedFish {
+    pub fn restore(self, world: &World) -> Option<CaughtFishInstance> {
+        match self {
+            Self::Caught(fish) => Some(fish),
+            Self::Legacy(fish_type) => world
+                .fish_configs
+                .configs
+                .get(fish_type)
+                .map(|config| CaughtFishInstance::average(fish_type, config)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub colors: PlayerColors,
+    pub money: u32,
+    pub boat_level: u8,
+    #[serde(default)]
+    pub rod_level: u8,
+    pub inventory: Vec<SavedFish>,
+    pub fishdex: HashSet<FishType>,
+    #[serde(default)]
+    pub baits: Vec<u32>,
+    #[serde(default)]
+    pub quests: Vec<Quest>,
+    #[serde(default)]
+    pub achievements: HashSet<Achievement>,
+}
+
+impl Profile {
+    pub fn new(player: &Player) -> Self {
+        Self {
+            name: player.name.clone(),
+            colors: player.colors.clone(),
+            money: player.money,
+            boat_level: player.boat_level,
+            rod_level: player.rod_level,
+            inventory: player
+                .inventory
+                .iter()
+                .copied()
+                .map(SavedFish::Caught)
+                .collect(),
+            fishdex: player.fishdex.clone(),
+            baits: player.baits.clone(),
+            quests: player.quests.clone(),
+            achievements: player.achievements.clone(),
+        }
+    }
+
+    pub fn apply(&self, world: &World, player: &mut Player) {
+        player.name = self.name.clone();
+        player.colors = self.colors.clone();
+        player.money = self.money;
+        player.boat_level = self.boat_level;
+        player.rod_level = self.rod_level;
+        player.inventory = self
+            .inventory
+            .iter()
+            .filter_map(|fish| fish.restore(world))
+            .collect();
+        player.fishdex = self.fishdex.clone();
+        player.baits = self.baits.clone();
+        // Bounties for fish that are not in this world anymore can't be shown
+        player.quests = self
+            .quests
+            .iter()
+            .filter(|quest| quest.bounty.goal.fish_type() < world.fish_configs.configs.len())
+            .cloned()
+            .collect();
+        player.achievements = self.achievements.clone();
+    }
+}
+
+// Keyed by the token the client generated for itself
+#[derive(Debug, Clone, Default)]
+pub struct ProfileStore {
+    path: Option<std::path::PathBuf>,
+    profiles: HashMap<String, Profile>,
+}
+
+impl ProfileStore {
+    pub fn open(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let profiles = match std::fs::File::open(&path) {
+            Ok(file) => serde_json::from_reader(std::io::BufReader::new(file))
+                .with_context(|| format!("Profiles in {path:?} are corrupted, move them away"))?,
+            Err(_) => HashMap::new(),
+        };
+        Ok(Self {
+            path: Some(path),
+            profiles,
+        })
+    }
+
+    pub fn get(&self, token: &str) -> Option<&Profile> {
+        self.profiles.get(token)
+    }
+
+    pub fn insert(&mut self, token: String, profile: Profile) {
+        self.profiles.insert(token, profile);
+    }
+
+    pub fn save(&self) {
+        let Some(path) = &self.path else { return };
+        let tmp_path = path.with_extension("tmp");
+        let save = || -> anyhow::Result<()> {
+            let file = std::fs::File::create(&tmp_path)?;
+            serde_json::to_writer(std::io::BufWriter::new(file), &self.profiles)?;
+            std::fs::rename(&tmp_path, path)?;
+            Ok(())
+        };
+        if let Err(e) = save() {
+            error!("Failed to save profiles to {path:?}: {e}");
+        }
+    }
+}
+
+impl Model {
+    // Links a connected player to their profile, restoring it if we have seen them before
+    pub fn login(&mut self, player_id: Id, token: String) -> Option<Player> {
+        if token.is_empty() || token.len() > MAX_TOKEN_LEN {
+            return None;
+        }
+        if self.tokens.contains_key(&player_id) || self.tokens.values().any(|t| *t == token) {
+            return None;
+        }
+        self.tokens.insert(player_id, token.clone());
+        let profile = self.profiles.get(&token)?;
+        let player = self.players.get_mut(&player_id)?;
+        profile.apply(&self.world, player);
+        Some(player.clone())
+    }
+
+    pub fn save_profiles(&mut self) {
+        if self.tokens.is_empty() {
+            return;
+        }
+        for (id, token) in self.tokens.iter() {
+            if let Some(player) = self.players.get(id) {
+                self.profiles.insert(token.clone(), Profile::new(player));
+            }
+        }
+        self.profiles.save();
+    }
+}
@@ -0,0 +1,455 @@
+use super::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuestConfig {
+    // Seconds between new bounties being posted
+    pub interval: f32,
+    // Seconds a bounty stays on the board
+    pub duration: f32,
+    pub per_shop: usize,
+    pub max_active: usize,
+    // Rewards relative to selling the fish
+    pub reward_multiplier: f32,
+}
+
+impl Default for QuestConfig {
+    fn default() -> Self {
+        Self {
+            interval: 90.0,
+            duration: 600.0,
+            per_shop: 2,
+            max_active: 3,
+            reward_multiplier: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BountyGoal {
+    Catch {
+        fish_type: FishType,
+        count: u32,
+    },
+    Trophy {
+        fish_type: FishType,
+        min_weight: f32,
+    },
+}
+
+impl BountyGoal {
+    pub fn fish_type(&self) -> FishType {
+        match *self {
+            Self::Catch { fish_type, .. } | Self::Trophy { fish_type, .. } => fish_type,
+        }
+    }
+
+    pub fn required(&self) -> u32 {
+        match *self {
+            Self::Catch { count, .. } => count,
+            Self::Trophy { .. } => 1,
+        }
+    }
+
+    pub fn counts(&self, fish: &CaughtFishInstance) -> bool {
+        match *self {
+            Self::Catch { fish_type, .. } => fish.fish_type == fish_type,
+            Self::Trophy {
+                fish_type,
+                min_weight,
+            } => fish.fish_type == fish_type && fish.weight >= min_weight,
+        }
+    }
+
+    pub fn description(&self, world: &World) -> String {
+        match *self {
+            Self::Catch { fish_type, count } => {
+                let name = &world.fish_configs.configs[fish_type].name;
+                format!("catch {count} {name}")
+            }
+            Self::Trophy {
+                fish_type,
+                min_weight,
+            } => {
+                let name = &world.fish_configs.configs[fish_type].name;
+                format!("catch a {name} over {min_weight:.1}kg")
+            }
+        }
+    }
+}
+
+// Posted at a fish shop for anyone to take
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bounty {
+    pub id: Id,
+    // Index of the fish shop
+    pub shop: usize,
+    pub goal: BountyGoal,
+    pub reward: u32,
+    pub expires: f32,
+}
+
+// A bounty taken by a player, it can still be finished after it is gone from the board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quest {
+    pub bounty: Bounty,
+    pub progress: u32,
+}
+
+impl Quest {
+    pub fn is_complete(&self) -> bool {
+        self.progress >= self.bounty.goal.required()
+    }
+}
+
+impl Model {
+    fn roll_bounty(&mut self, shop: usize) -> Bounty {
+        let config = &self.world.config.quests;
+        let fish_type = self.rng.gen_range(0..self.world.fish_configs.configs.len());
+        let fish_config = &self.world.fish_configs.configs[fish_type];
+        let (goal, value) = if self.rng.gen_bool(0.5) {
+            let count = self.rng.gen_range(2..=4);
+            let goal = BountyGoal::Catch { fish_type, count };
+            (goal, fish_config.cost * count)
+        } else {
+            let catch = &fish_config.catch;
+            let min_weight = catch.min_weight + (catch.max_weight - catch.min_weight) * 0.7;
+            let goal = BountyGoal::Trophy {
+                fish_type,
+                min_weight,
+            };
+            (goal, fish_config.cost * 3)
+        };
+        Bounty {
+            id: self.id_gen.gen(),
+            shop,
+            goal,
+            reward: ((value as f32 * config.reward_multiplier).round() as u32).max(1),
+            expires: self.time + config.duration,
+        }
+    }
+
+    pub fn post_bounties(&mut self) {
+        if self.world.fish_configs.configs.is_empty() {
+            return;
+        }
+        for shop in 0..self.world.config.fish_shops.len() {
+            let posted = self.bounties.iter().filter(|b| b.shop == shop).count();
+            if posted < self.world.config.quests.per_shop {
+                let bounty = self.roll_bounty(shop);
+                self.bounties.push(bounty);
+            }
+        }
+    }
+
+    pub fn update_bounties(&mut self, delta_time: f32) {
+        let time = self.time;
+        self.bounties.retain(|bounty| bounty.expires > time);
+        if self.every(self.world.config.quests.interval, delta_time) {
+            self.post_bounties();
+        }
+    }
+
+    // Counts a caught fish towards the quests of the player
+    pub fn track_quests(
+        &mut self,
+        player_id: Id,
+        fish: &CaughtFishInstance,
+        events: &mut Vec<Event>,
+    ) {
+        let Some(player) = self.players.get_mut(&player_id) else { return };
+        for quest in &mut player.quests {
+            if quest.is_complete() || !quest.bounty.goal.counts(fish) {
+                continue;
+            }
+            quest.progress += 1;
+            if quest.is_complete() {
+                events.push(Event::QuestCompleted {
+                    player: player_id,
+                    bounty: quest.bounty.id,
+                });
+            }
+        }
+    }
+
+    pub fn accept_bounty(
+        &mut self,
+        player_id: Id,
+        bounty_id: Id,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let bounty = self
+            .bounties
+            .iter()
+            .find(|bounty| bounty.id == bounty_id)
+            .ok_or(TransactionError::InvalidBounty)?;
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        if player
+            .quests
+            .iter()
+            .any(|quest| quest.bounty.id == bounty_id)
+        {
+            return Err(TransactionError::AlreadyTaken);
+        }
+        if player.quests.len() >= self.world.config.quests.max_active {
+            return Err(TransactionError::TooManyQuests);
+        }
+        let shop = self.world.config.fish_shops[bounty.shop];
+        if (shop - player.pos.pos).len() > SHOPPING_DISTANCE {
+            return Err(TransactionError::TooFarFromShop);
+        }
+        player.quests.push(Quest {
+            bounty: bounty.clone(),
+            progress: 0,
+        });
+        events.push(Event::AcceptedBounty {
+            player: player_id,
+            bounty: bounty_id,
+        });
+        Ok(())
+    }
+
+    // Pays out a finished quest at the shop that posted it
+    pub fn claim_bounty(
+        &mut self,
+        player_id: Id,
+        bounty_id: Id,
+        events: &mut Vec<Event>,
+    ) -> Result<(), TransactionError> {
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(TransactionError::UnknownPlayer)?;
+        let index = player
+            .quests
+            .iter()
+            .position(|quest| quest.bounty.id == bounty_id)
+            .ok_or(TransactionError::InvalidBounty)?;
+        let quest = &player.quests[index];
+        if !quest.is_complete() {
+            return Err(TransactionError::QuestNotComplete);
+        }
+        let shop = self.world.config.fish_shops.get(quest.bounty.shop).copied();
+        let at_shop = shop.map_or(false, |shop| {
+            (shop - player.pos.pos).len() <= SHOPPING_DISTANCE
+        });
+        if !at_shop {
+            return Err(TransactionError::TooFarFromShop);
+        }
+        let quest = player.quests.remove(index);
+        player.money += quest.bounty.reward;
+        events.push(Event::ClaimedBounty {
+            player: player_id,
+            reward: quest.bounty.reward,
+        });
+        events.push(Event::Sound {
+            player: None,
+            sound_type: SoundType::SellFish,
+            pos: player.pos.pos,
+        });
+        Ok(())
+    }
+}
+
+impl Game {
+    pub fn nearby_fish_shop(&self) -> Option<usize> {
+        self.world
+            .config
+            .fish_shops
+            .iter()
+            .position(|&pos| (pos - self.player.pos.pos).len() < SHOPPING_DISTANCE)
+    }
+
+    // Q at a fish shop claims the finished quests of the shop, or takes its next bounty
+    pub fn quest_key(&mut self) {
+        let Some(shop) = self.nearby_fish_shop() else { return };
+        self.model.send(Message::Update(self.player.clone()));
+        let finished: Vec<Id> = self
+            .player
+            .quests
+            .iter()
+            .filter(|quest| quest.bounty.shop == shop && quest.is_complete())
+            .map(|quest| quest.bounty.id)
+            .collect();
+        if !finished.is_empty() {
+            for bounty in finished {
+                self.model.send(Message::ClaimBounty { bounty });
+            }
+            return;
+        }
+        if let Some(bounty) = self.open_bounty(shop) {
+            self.model.send(Message::AcceptBounty { bounty: bounty.id });
+        }
+    }
+
+    // The first bounty of the shop the player has not taken yet
+    fn open_bounty(&self, shop: usize) -> Option<Bounty> {
+        let model = self.model.get();
+        let bounty = model.bounties.iter().find(|bounty| {
+            bounty.shop == shop
+                && !self
+                    .player
+                    .quests
+                    .iter()
+                    .any(|quest| quest.bounty.id == bounty.id)
+        })?;
+        Some(bounty.clone())
+    }
+
+    pub fn bounty_text(&self) -> String {
+        let Some(shop) = self.nearby_fish_shop() else { return String::new() };
+        if let Some(quest) = self
+            .player
+            .quests
+            .iter()
+            .find(|quest| quest.bounty.shop == shop && quest.is_complete())
+        {
+            return format!(
+                "press Q to claim ${} for your bounty\n",
+                quest.bounty.reward
+            );
+        }
+        match self.open_bounty(shop) {
+            Some(bounty) => format!(
+                "press Q to take a bounty: {} for ${}\n",
+                bounty.goal.description(&self.world),
+                bounty.reward,
+            ),
+            None => String::new(),
+        }
+    }
+
+    pub fn draw_quests(&self, framebuffer: &mut ugli::Framebuffer, camera: &geng::Camera2d) {
+        let text: Vec<String> = self
+            .player
+            .quests
+            .iter()
+            .map(|quest| {
+                format!(
+                    "{} {}/{}",
+                    quest.bounty.goal.description(&self.world),
+                    quest.progress.min(quest.bounty.goal.required()),
+                    quest.bounty.goal.required(),
+                )
+            })
+            .collect();
+        self.draw_text(
+            framebuffer,
+            camera,
+            &text.join("\n"),
+            vec2(5.0, camera.fov / 2.0 - 2.0),
+        );
+    }
+}
+
+#[test]
+fn test_accept_bounty() {
+    let mut model = Model::seeded(WorldRef::default_world(), 3);
+    let mut events = Vec::new();
+    let player_id = model.id_gen.gen();
+    let shop = model.world.config.fish_shops[0];
+    model
+        .players
+        .insert(Player::new(player_id, shop + vec2(10.0, 0.0)));
+    let bounty = model
+        .bounties
+        .iter()
+        .find(|bounty| bounty.shop == 0)
+        .unwrap()
+        .id;
+    assert_eq!(
+        model.accept_bounty(player_id, bounty, &mut events),
+        Err(TransactionError::TooFarFromShop),
+    );
+    model.players.get_mut(&player_id).unwrap().pos.pos = shop;
+    model.accept_bounty(player_id, bounty, &mut events).unwrap();
+    assert_eq!(
+        model.accept_bounty(player_id, bounty, &mut events),
+        Err(TransactionError::AlreadyTaken),
+    );
+    assert_eq!(
+        model.accept_bounty(player_id, Id(u64::MAX), &mut events),
+        Err(TransactionError::InvalidBounty),
+    );
+    assert_eq!(
+        model.accept_bounty(Id(u64::MAX), bounty, &mut events),
+        Err(TransactionError::UnknownPlayer),
+    );
+}
+
+#[test]
+fn test_claim_bounty() {
+    let mut model = Model::seeded(WorldRef::default_world(), 3);
+    let mut events = Vec::new();
+    let player_id = model.id_gen.gen();
+    let mut player = Player::new(player_id, model.world.config.fish_shops[0]);
+    let bounty = Bounty {
+        id: model.id_gen.gen(),
+        shop: 0,
+        goal: BountyGoal::Catch {
+            fish_type: 0,
+            count: 2,
+        },
+        reward: 50,
+        expires: 100.0,
+    };
+    player.quests.push(Quest {
+        bounty: bounty.clone(),
+        progress: 0,
+    });
+    model.players.insert(player);
+
+    let fish = CaughtFishInstance::average(0, &model.world.fish_configs.configs[0]);
+    model.track_quests(player_id, &fish, &mut events);
+    assert_eq!(
+        model.claim_bounty(player_id, bounty.id, &mut events),
+        Err(TransactionError::QuestNotComplete),
+    );
+    model.track_quests(player_id, &fish, &mut events);
+    model
+        .claim_bounty(player_id, bounty.id, &mut events)
+        .unwrap();
+    let player = model.players.get(&player_id).unwrap();
+    assert_eq!(player.money, 50);
+    assert!(player.quests.is_empty());
+}
+
+#[test]
+fn test_bounties_expire() {
+    let mut model = Model::seeded(WorldRef::default_world(), 3);
+    let first = model.bounties[0].clone();
+    model.time = first.expires;
+    model.update_bounties(0.1);
+    assert!(!model.bounties.contains(&first));
+    assert!(model
+        .bounties
+        .iter()
+        .all(|bounty| bounty.expires > model.time));
+}
+
+#[test]
+fn test_restore_quests() {
+    let world = WorldRef::default_world();
+    let quest = |fish_type| Quest {
+        bounty: Bounty {
+            id: Id(0),
+            shop: 0,
+            goal: BountyGoal::Catch {
+                fish_type,
+                count: 1,
+            },
+            reward: 10,
+            expires: 100.0,
+        },
+        progress: 0,
+    };
+    let mut player = Player::new(Id(0), Vec2::ZERO);
+    player.quests = vec![quest(0), quest(world.fish_configs.configs.len())];
+    let profile = Profile::new(&player);
+    let mut restored = Player::new(Id(1), Vec2::ZERO);
+    profile.apply(&world, &mut restored);
+    assert_eq!(restored.quests, vec![quest(0)]);
+}
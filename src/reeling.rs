@@ -0,0 +1,224 @@
+use super::*;
+
+// The fish is caught once the bobber is pulled this close
+pub const LANDING_DISTANCE: f32 = 1.0;
+// How fast the line is pulled in while holding the button
+pub const REEL_SPEED: f32 = 1.5;
+// Strongest pull of any fish behavior, relative to the fish's strength
+pub const MAX_PULL: f32 = 1.5;
+// The fish shakes off the hook if the line stays slacker than this for too long
+pub const MIN_TENSION: f32 = 0.15;
+const MAX_SLACK_TIME: f32 = 1.5;
+const TENSION_RISE: f32 = 0.5;
+const TENSION_DECAY: f32 = 0.6;
+// Above this the fish gets tired
+const TIRING_TENSION: f32 = 0.5;
+
+// How hard a fish fights when hooked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StruggleConfig {
+    // How fast the fish drags the bobber away
+    pub strength: f32,
+    // Seconds of a tight line before the fish gives up
+    pub stamina: f32,
+}
+
+impl Default for StruggleConfig {
+    fn default() -> Self {
+        Self {
+            strength: 0.5,
+            stamina: 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StruggleOutcome {
+    Fighting,
+    Landed,
+    Escaped,
+    Snapped,
+}
+
+// A hooked fish fighting the line
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Struggle {
+    pub fish: Id,
+    pub bobber_pos: Vec2<f32>,
+    // 0 is a slack line, the line snaps at 1
+    pub tension: f32,
+    // Goes from 1 down to 0 as the fish gets tired
+    pub stamina: f32,
+    pub slack_time: f32,
+    pub time: f32,
+}
+
+impl Struggle {
+    pub fn new(fish: Id, bobber_pos: Vec2<f32>) -> Self {
+        Self {
+            fish,
+            bobber_pos,
+            tension: 0.5,
+            stamina: 1.0,
+            slack_time: 0.0,
+            time: 0.0,
+        }
+    }
+
+    // How hard the fish is pulling right now, relative to its strength
+    pub fn pull(&self, behavior: &FishBehavior) -> f32 {
+        let t = self.time;
+        let pattern = match behavior {
+            FishBehavior::Chaos => ((t * 7.3).sin() * (t * 3.1).cos()).abs() * MAX_PULL,
+            FishBehavior::Idle => 0.5,
+            _ => 0.6 + 0.4 * (t * 2.0).sin(),
+        };
+        pattern * (0.3 + 0.7 * self.stamina)
+    }
+
+    pub fn update(
+        &mut self,
+        player_pos: Vec2<f32>,
+        config: &FishConfig,
+        reel_strength: f32,
+        reeling: bool,
+        delta_time: f32,
+    ) -> StruggleOutcome {
+        self.time += delta_time;
+        let pull = self.pull(&config.spawn_circle.behavior);
+        let to_player = (player_pos - self.bobber_pos).normalize_or_zero();
+        self.bobber_pos -= to_player * pull * config.struggle.strength * delta_time;
+        if reeling {
+            self.bobber_pos += to_player * REEL_SPEED * reel_strength * delta_time;
+            self.tension += TENSION_RISE * (0.5 + pull) / reel_strength * delta_time;
+        } else {
+            self.tension = (self.tension - TENSION_DECAY * delta_time).max(0.0);
+        }
+        if self.tension > TIRING_TENSION {
+            self.stamina = (self.stamina - delta_time / config.struggle.stamina).max(0.0);
+        }
+        if self.tension < MIN_TENSION {
+            self.slack_time += delta_time;
+        } else {
+            self.slack_time = 0.0;
+        }
+
+        if self.tension >= 1.0 {
+            StruggleOutcome::Snapped
+        } else if self.slack_time > MAX_SLACK_TIME {
+            StruggleOutcome::Escaped
+        } else if (player_pos - self.bobber_pos).len() < LANDING_DISTANCE {
+            StruggleOutcome::Landed
+        } else {
+            StruggleOutcome::Fighting
+        }
+    }
+}
+
+impl Game {
+    // Plays the hooked fish against the held mouse button
+    pub fn update_struggle(&mut self, delta_time: f32) {
+        let FishingState::Struggling(mut struggle) = self.player.fishing_state else { return };
+        let Some(fish) = self
+            .model
+            .get()
+            .fishes
+            .get(&struggle.fish)
+            .map(|fish| fish.index)
+        else {
+            self.player.fishing_state = FishingState::Idle;
+            return;
+        };
+        let reeling = self
+            .geng
+            .window()
+            .is_button_pressed(geng::MouseButton::Left);
+        let config = &self.world.fish_configs.configs[fish];
+        let reel_strength = self.world.config.rod(self.player.rod_level).reel_strength;
+        match struggle.update(
+            self.player.pos.pos,
+            config,
+            reel_strength,
+            reeling,
+            delta_time,
+        ) {
+            StruggleOutcome::Fighting => {
+                self.player.fishing_state = FishingState::Struggling(struggle);
+            }
+            StruggleOutcome::Landed => {
+                self.player.fishing_state = FishingState::Struggling(struggle);
+                // The server checks the landing against our last update
+                self.model.send(Message::Update(self.player.clone()));
+                self.model.send(Message::Catch(struggle.fish));
+                self.player.fishing_state = FishingState::Idle;
+                self.play_sound(self.player.pos.pos, SoundType::Ding);
+            }
+            StruggleOutcome::Escaped => {
+                self.player.fishing_state = FishingState::Idle;
+                self.tutorial = "the fish got away, keep the line tight".to_owned();
+                self.tutorial_timer = 3.0;
+                self.play_sound_for_everyone(self.player.pos.pos, ClientSound::StopFishing);
+            }
+            StruggleOutcome::Snapped => {
+                self.player.fishing_state = FishingState::Idle;
+                self.tutorial = "the line snapped, don't pull so hard".to_owned();
+                self.tutorial_timer = 3.0;
+                self.play_sound_for_everyone(self.player.pos.pos, ClientSound::StopFishing);
+            }
+        }
+    }
+
+    pub fn draw_tension(&self, framebuffer: &mut ugli::Framebuffer) {
+        let FishingState::Struggling(struggle) = &self.player.fishing_state else { return };
+        let camera = geng::Camera2d {
+            center: Vec2::ZERO,
+            rotation: 0.0,
+            fov: 10.0,
+        };
+        let half_size = vec2(2.0, 0.15);
+        let bar = AABB::point(vec2(0.0, -2.5)).extend_symmetric(half_size);
+        self.geng
+            .draw_2d(framebuffer, &camera, &draw_2d::Quad::new(bar, Rgba::BLACK));
+        let color = if struggle.tension > 0.8 || struggle.tension < MIN_TENSION {
+            Rgba::RED
+        } else {
+            Rgba::GREEN
+        };
+        let filled = struggle.tension.min(1.0);
+        let filled = AABB::point(vec2(half_size.x * (filled - 1.0), -2.5))
+            .extend_symmetric(vec2(half_size.x * filled, half_size.y));
+        self.geng
+            .draw_2d(framebuffer, &camera, &draw_2d::Quad::new(filled, color));
+    }
+}
+
+#[test]
+fn test_struggle() {
+    let config: FishConfig = serde_json::from_str(
+        r#"{
+            "name": "Test fish",
+            "cost": 10,
+            "size": 1,
+            "spawn_circle": { "center": [0, 0], "radius": 5, "behavior": "Orbit" },
+            "count": 1,
+            "struggle": { "strength": 0.5, "stamina": 2 }
+        }"#,
+    )
+    .unwrap();
+    let fight = |reel: &dyn Fn(&Struggle) -> bool| {
+        let mut struggle = Struggle::new(Id(0), vec2(5.0, 0.0));
+        for _ in 0..1000 {
+            let outcome = struggle.update(Vec2::ZERO, &config, 1.0, reel(&struggle), 0.1);
+            if outcome != StruggleOutcome::Fighting {
+                return outcome;
+            }
+        }
+        StruggleOutcome::Fighting
+    };
+    assert_eq!(fight(&|_| true), StruggleOutcome::Snapped);
+    assert_eq!(fight(&|_| false), StruggleOutcome::Escaped);
+    assert_eq!(
+        fight(&|struggle| struggle.tension < 0.7),
+        StruggleOutcome::Landed,
+    );
+}
@@ -0,0 +1,314 @@
+use super::*;
+
+// Tries at picking a random point of a polygon's bounds before giving up on it
+const SAMPLE_TRIES: usize = 1000;
+// How far around a fish a map mask is searched for the way home
+const MASK_SEARCH_DISTANCE: f32 = 20.0;
+const MASK_SEARCH_STEP: f32 = 1.0;
+const MASK_SEARCH_DIRECTIONS: usize = 16;
+
+// An area a species lives in, for shapes that a circle does not describe well
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SpawnRegion {
+    Circle {
+        center: Vec2<f32>,
+        radius: f32,
+        #[serde(default)]
+        inner_radius: f32,
+    },
+    Polygon {
+        points: Vec<Vec2<f32>>,
+    },
+    Union {
+        regions: Vec<SpawnRegion>,
+    },
+    // Where a channel of the heightmap image is within the range, 0 to 1
+    Mask {
+        channel: usize,
+        min: f32,
+        max: f32,
+        // Pixels of the map inside the mask, filled in by prepare
+        #[serde(skip)]
+        points: Vec<Vec2<f32>>,
+    },
+}
+
+fn pixel_size(map: &Map) -> f32 {
+    2.0 * map.size / map.get_dimensions().0 as f32
+}
+
+fn polygon_area(points: &[Vec2<f32>]) -> f32 {
+    // Shoelace formula
+    let twice_area: f32 = (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    twice_area.abs() / 2.0
+}
+
+impl SpawnRegion {
+    // Checks the region and looks up the pixels of masks once instead of on every spawn
+    pub fn prepare(&mut self, map: &Map) -> anyhow::Result<()> {
+        match self {
+            Self::Circle {
+                radius,
+                inner_radius,
+                ..
+            } => {
+                if inner_radius > radius {
+                    anyhow::bail!("A spawn circle can't be smaller on the inside");
+                }
+            }
+            Self::Polygon { points } => {
+                if points.len() < 3 {
+                    anyhow::bail!("A spawn polygon needs at least 3 points");
+                }
+                if polygon_area(points) <= 0.0 {
+                    anyhow::bail!("A spawn polygon needs to cover some area");
+                }
+            }
+            Self::Union { regions } => {
+                if regions.is_empty() {
+                    anyhow::bail!("A spawn union needs at least one region");
+                }
+                for region in regions {
+                    region.prepare(map)?;
+                }
+            }
+            Self::Mask {
+                channel,
+                min,
+                max,
+                points,
+            } => {
+                if *channel > 3 {
+                    anyhow::bail!("The map image has no channel {channel}");
+                }
+                let (channel, min, max) = (*channel, *min, *max);
+                let (width, height) = map.get_dimensions();
+                let pixel_size = pixel_size(map);
+                *points = (0..width)
+                    .flat_map(|x| (0..height).map(move |y| vec2(x, y)))
+                    .map(|pixel| pixel.map(|x| x as f32 * pixel_size - map.size))
+                    .filter(|&pos| (min..=max).contains(&map.get_channel_value(channel, pos)))
+                    .collect();
+                if points.is_empty() {
+                    anyhow::bail!("The mask does not cover any part of the map");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, map: &Map, pos: Vec2<f32>) -> bool {
+        match self {
+            Self::Circle {
+                center,
+                radius,
+                inner_radius,
+            } => {
+                let distance = (pos - *center).len();
+                *inner_radius <= distance && distance <= *radius
+            }
+            Self::Polygon { points } => {
+                // Even-odd rule, counting the edges a ray to the right crosses
+                let mut inside = false;
+                for (i, &a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > pos.y) != (b.y > pos.y)
+                        && pos.x < a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+            Self::Union { regions } => regions.iter().any(|region| region.contains(map, pos)),
+            Self::Mask {
+                channel, min, max, ..
+            } => (*min..=*max).contains(&map.get_channel_value(*channel, pos)),
+        }
+    }
+
+    fn area(&self, map: &Map) -> f32 {
+        match self {
+            Self::Circle {
+                radius,
+                inner_radius,
+                ..
+            } => f32::PI * (radius.powi(2) - inner_radius.powi(2)),
+            Self::Polygon { points } => polygon_area(points),
+            Self::Union { regions } => regions.iter().map(|region| region.area(map)).sum(),
+            Self::Mask { points, .. } => points.len() as f32 * pixel_size(map).powi(2),
+        }
+    }
+
+    pub fn sample(&self, map: &Map, rng: &mut impl Rng) -> Option<Vec2<f32>> {
+        match self {
+            Self::Circle {
+                center,
+                radius,
+                inner_radius,
+            } => {
+                let r = rng.gen_range(inner_radius.powi(2)..=radius.powi(2)).sqrt();
+                let angle = rng.gen_range(0.0..(f32::PI * 2.0));
+                Some(*center + vec2(r, 0.0).rotate(angle))
+            }
+            Self::Polygon { points } => {
+                let (min, max) =
+                    points
+                        .iter()
+                        .fold((points[0], points[0]), |(min, max), &point| {
+                            (
+                                vec2(min.x.min(point.x), min.y.min(point.y)),
+                                vec2(max.x.max(point.x), max.y.max(point.y)),
+                            )
+                        });
+                for _ in 0..SAMPLE_TRIES {
+                    let pos = vec2(rng.gen_range(min.x..=max.x), rng.gen_range(min.y..=max.y));
+                    if self.contains(map, pos) {
+                        return Some(pos);
+                    }
+                }
+                None
+            }
+            // Bigger parts get more of the fish
+            Self::Union { regions } => regions
+                .choose_weighted(&mut *rng, |region| region.area(map))
+                .ok()?
+                .sample(map, rng),
+            Self::Mask { points, .. } => {
+                let point = *points.choose(&mut *rng)?;
+                // Spread around the pixel, as long as that stays inside the mask
+                let offset = vec2(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5));
+                let pos = point + offset * pixel_size(map);
+                Some(if self.contains(map, pos) { pos } else { point })
+            }
+        }
+    }
+
+    // The nearest point of the region to swim back to
+    pub fn closest_point(&self, map: &Map, pos: Vec2<f32>) -> Option<Vec2<f32>> {
+        match self {
+            Self::Circle {
+                center,
+                radius,
+                inner_radius,
+            } => {
+                let offset = pos - *center;
+                let distance = offset.len().clamp(*inner_radius, *radius);
+                Some(*center + offset.normalize_or_zero() * distance)
+            }
+            Self::Polygon { points } => (0..points.len())
+                .map(|i| pos + to_segment(points[i], points[(i + 1) % points.len()], pos))
+                .min_by_key(|point| r32((*point - pos).len())),
+            Self::Union { regions } => regions
+                .iter()
+                .filter_map(|region| region.closest_point(map, pos))
+                .min_by_key(|point| r32((*point - pos).len())),
+            Self::Mask { .. } => {
+                // No shape to project onto, so look around in growing rings
+                let mut distance = MASK_SEARCH_STEP;
+                while distance <= MASK_SEARCH_DISTANCE {
+                    for i in 0..MASK_SEARCH_DIRECTIONS {
+                        let angle = i as f32 / MASK_SEARCH_DIRECTIONS as f32 * 2.0 * f32::PI;
+                        let point = pos + vec2(distance, 0.0).rotate(angle);
+                        if self.contains(map, point) {
+                            return Some(point);
+                        }
+                    }
+                    distance += MASK_SEARCH_STEP;
+                }
+                None
+            }
+        }
+    }
+}
+
+impl SpawnCircle {
+    // A random point of the area the species spawns in
+    pub fn sample(&self, map: &Map, rng: &mut impl Rng) -> Vec2<f32> {
+        if let Some(region) = &self.region {
+            return region.sample(map, rng).unwrap_or(self.center);
+        }
+        let inner_radius = self.inner_radius.unwrap_or(0.0);
+        // polar coordinates because we're fancy, the square root spreads them evenly over the area
+        let r = rng
+            .gen_range(inner_radius.powi(2)..self.radius.powi(2))
+            .sqrt();
+        let angle = rng.gen_range(0.0..(f32::PI * 2.0));
+        vec2(
+            self.center.x + r * angle.cos(),
+            self.center.y + r * angle.sin(),
+        )
+    }
+}
+
+#[test]
+fn test_spawn_regions() {
+    let manifest = MapManifest {
+        name: "test regions".to_owned(),
+        heightmap: default(),
+        colormap: default(),
+        size: 10.0,
+        deepest_depth: -1.5,
+        highest_land: 0.5,
+        fish_list: default(),
+        fish_shops: vec![],
+        boat_shops: vec![vec![]; 3],
+    };
+    // The green channel marks the left half of the map
+    let image = image::RgbaImage::from_fn(8, 8, |x, _| {
+        image::Rgba([0, if x < 4 { 0xff } else { 0 }, 0, 0xff])
+    });
+    let map = Map::new(&manifest, image.clone(), image);
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let mut region: SpawnRegion = serde_json::from_str(
+        r#"{ "Union": { "regions": [
+            { "Polygon": { "points": [[0, 0], [4, 0], [0, 4]] } },
+            { "Circle": { "center": [-5, -5], "radius": 1 } }
+        ] } }"#,
+    )
+    .unwrap();
+    region.prepare(&map).unwrap();
+    assert!(region.contains(&map, vec2(1.0, 1.0)));
+    assert!(!region.contains(&map, vec2(3.0, 3.0)));
+    assert!(region.contains(&map, vec2(-5.5, -5.0)));
+    for _ in 0..100 {
+        let pos = region.sample(&map, &mut rng).unwrap();
+        assert!(region.contains(&map, pos));
+    }
+    let home = region.closest_point(&map, vec2(3.0, 3.0)).unwrap();
+    assert!((home - vec2(2.0, 2.0)).len() < 1e-4);
+    let home = region.closest_point(&map, vec2(-8.0, -5.0)).unwrap();
+    assert!((home - vec2(-6.0, -5.0)).len() < 1e-4);
+    let mut empty = SpawnRegion::Union { regions: vec![] };
+    assert!(empty.prepare(&map).is_err());
+    let mut flat = SpawnRegion::Polygon {
+        points: vec![vec2(0.0, 0.0), vec2(1.0, 1.0), vec2(2.0, 2.0)],
+    };
+    assert!(flat.prepare(&map).is_err());
+
+    let mask = |channel, min| SpawnRegion::Mask {
+        channel,
+        min,
+        max: 1.0,
+        points: vec![],
+    };
+    let mut region = mask(1, 0.5);
+    region.prepare(&map).unwrap();
+    assert!(region.contains(&map, vec2(-5.0, 0.0)));
+    assert!(!region.contains(&map, vec2(5.0, 0.0)));
+    for _ in 0..100 {
+        let pos = region.sample(&map, &mut rng).unwrap();
+        assert!(region.contains(&map, pos));
+    }
+    let home = region.closest_point(&map, vec2(5.0, 0.0)).unwrap();
+    assert!(region.contains(&map, home));
+    assert!(mask(4, 0.5).prepare(&map).is_err());
+    // Nothing is brighter than white
+    assert!(mask(1, 1.5).prepare(&map).is_err());
+}
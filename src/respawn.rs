@@ -0,0 +1,104 @@
+use super::*;
+
+// How a species refills after being fished out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RespawnConfig {
+    // Average seconds until one missing fish comes back
+    pub delay: f32,
+    // Random variation of the delay, relative to it
+    pub jitter: f32,
+}
+
+impl Default for RespawnConfig {
+    fn default() -> Self {
+        Self {
+            delay: 45.0,
+            jitter: 0.5,
+        }
+    }
+}
+
+impl Model {
+    fn roll_respawn_delay(&mut self, fish_type: FishType) -> f32 {
+        let config = &self.world.fish_configs.configs[fish_type].respawn;
+        let jitter = config.jitter.clamp(0.0, 1.0);
+        config.delay * self.rng.gen_range(1.0 - jitter..=1.0 + jitter)
+    }
+
+    // Brings back one missing fish of each species whenever its timer runs out
+    pub fn update_respawns(&mut self) {
+        let mut populations = vec![0; self.world.fish_configs.configs.len()];
+        for fish in &self.fishes {
+            if let Some(population) = populations.get_mut(fish.index) {
+                *population += 1;
+            }
+        }
+        for (fish_type, population) in populations.into_iter().enumerate() {
+            let count = self.world.fish_configs.configs[fish_type].count as usize;
+            if population >= count {
+                self.respawn_times.remove(&fish_type);
+                continue;
+            }
+            match self.respawn_times.get(&fish_type).copied() {
+                None => {
+                    let time = self.time + self.roll_respawn_delay(fish_type);
+                    self.respawn_times.insert(fish_type, time);
+                }
+                Some(time) if time <= self.time => {
+                    // The next tick schedules another one if the species is still short
+                    self.respawn_times.remove(&fish_type);
+                    self.spawn_fish(fish_type);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn test_respawn_delay() {
+    let mut model = Model::seeded(WorldRef::default_world(), 11);
+    let config = &model.world.fish_configs.configs[0];
+    let count = config.count as usize;
+    let max_delay = config.respawn.delay * (1.0 + config.respawn.jitter);
+    let fish = model.fishes.iter().find(|fish| fish.index == 0).unwrap().id;
+    model.fishes.remove(&fish);
+    model.update_respawns();
+    assert_eq!(model.population(0), count - 1);
+
+    let mut seconds = 0.0;
+    while model.population(0) < count {
+        model.time += 1.0;
+        seconds += 1.0;
+        model.update_respawns();
+        assert!(seconds <= max_delay + 1.0);
+    }
+}
+
+#[test]
+fn test_respawns_stop_at_count() {
+    let mut model = Model::seeded(WorldRef::default_world(), 11);
+    let count = model.world.fish_configs.configs[0].count as usize;
+    for _ in 0..1000 {
+        model.time += 1.0;
+        model.update_respawns();
+    }
+    assert_eq!(model.population(0), count);
+}
+
+#[test]
+fn test_respawns_unknown_species() {
+    let mut model = Model::seeded(WorldRef::default_world(), 11);
+    let species = model.world.fish_configs.configs.len();
+    let populations: Vec<usize> = (0..species).map(|index| model.population(index)).collect();
+    let unknown = species;
+    model
+        .fishes
+        .insert(Fish::new(model.id_gen.gen(), unknown, Vec2::ZERO));
+    model.update_respawns();
+    for (index, population) in populations.into_iter().enumerate() {
+        assert_eq!(model.population(index), population);
+    }
+    assert!(!model.respawn_times.contains_key(&unknown));
+}
@@ -0,0 +1,173 @@
+use super::*;
+
+pub type Segment = [Vec2<f32>; 2];
+
+// From the point to the closest point of the segment
+pub fn to_segment(p1: Vec2<f32>, p2: Vec2<f32>, point: Vec2<f32>) -> Vec2<f32> {
+    if Vec2::dot(point - p1, p2 - p1) < 0.0 {
+        return p1 - point;
+    }
+    if Vec2::dot(point - p2, p1 - p2) < 0.0 {
+        return p2 - point;
+    }
+    let n = (p2 - p1).rotate_90();
+    // dot(point + n * t - p1, n) = 0
+    // dot(point - p1, n) + dot(n, n) * t = 0
+    let t = Vec2::dot(p1 - point, n) / Vec2::dot(n, n);
+    n * t
+}
+
+pub struct NearestSegment {
+    pub index: usize,
+    // From the query point to the closest point of the segment
+    pub delta: Vec2<f32>,
+    pub distance: f32,
+}
+
+// Uniform grid over segments for nearest segment queries
+pub struct SegmentGrid {
+    segments: Vec<Segment>,
+    cell_size: f32,
+    origin: Vec2<f32>,
+    width: i32,
+    height: i32,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SegmentGrid {
+    pub const DEFAULT_CELL_SIZE: f32 = 2.0;
+
+    pub fn new(segments: Vec<Segment>, cell_size: f32) -> Self {
+        let mut min = vec2(f32::INFINITY, f32::INFINITY);
+        let mut max = vec2(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for p in segments.iter().flatten() {
+            min = vec2(min.x.min(p.x), min.y.min(p.y));
+            max = vec2(max.x.max(p.x), max.y.max(p.y));
+        }
+        if segments.is_empty() {
+            min = Vec2::ZERO;
+            max = Vec2::ZERO;
+        }
+        let width = ((max.x - min.x) / cell_size).floor() as i32 + 1;
+        let height = ((max.y - min.y) / cell_size).floor() as i32 + 1;
+        let mut result = Self {
+            segments: Vec::new(),
+            cell_size,
+            origin: min,
+            width,
+            height,
+            cells: vec![Vec::new(); (width * height) as usize],
+        };
+        for (index, &[p1, p2]) in segments.iter().enumerate() {
+            let (x1, y1) = result.cell(vec2(p1.x.min(p2.x), p1.y.min(p2.y)));
+            let (x2, y2) = result.cell(vec2(p1.x.max(p2.x), p1.y.max(p2.y)));
+            for x in x1..=x2 {
+                for y in y1..=y2 {
+                    let cell = result.cell_index(x, y).unwrap();
+                    result.cells[cell].push(index);
+                }
+            }
+        }
+        result.segments = segments;
+        result
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    fn cell(&self, pos: Vec2<f32>) -> (i32, i32) {
+        let cell = (pos - self.origin).map(|x| (x / self.cell_size).floor() as i32);
+        (cell.x, cell.y)
+    }
+
+    fn cell_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+
+    pub fn nearest(&self, point: Vec2<f32>) -> Option<NearestSegment> {
+        if self.segments.is_empty() {
+            return None;
+        }
+        let (cx, cy) = self.cell(point);
+        // No need to look further than the whole grid
+        let max_ring = [cx, cy, self.width - 1 - cx, self.height - 1 - cy]
+            .into_iter()
+            .map(|x| x.abs())
+            .max()
+            .unwrap();
+        // Rings closer than the grid have no cells in them
+        let outside = |c: i32, size: i32| (-c).max(c - (size - 1)).max(0);
+        let first_ring = outside(cx, self.width).max(outside(cy, self.height));
+        let mut best: Option<NearestSegment> = None;
+        for ring in first_ring..=max_ring {
+            // Only the cells on the border of the ring, clipped to the grid
+            let step = (2 * ring).max(1) as usize;
+            for y in (cy - ring..=cy + ring).step_by(step) {
+                for x in (cx - ring).max(0)..=(cx + ring).min(self.width - 1) {
+                    self.check_cell(x, y, point, &mut best);
+                }
+            }
+            for x in (cx - ring..=cx + ring).step_by(step) {
+                for y in (cy - ring + 1).max(0)..=(cy + ring - 1).min(self.height - 1) {
+                    self.check_cell(x, y, point, &mut best);
+                }
+            }
+            // Everything in the next rings is at least this far away
+            if let Some(best) = &best {
+                if best.distance <= ring as f32 * self.cell_size {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    fn check_cell(&self, x: i32, y: i32, point: Vec2<f32>, best: &mut Option<NearestSegment>) {
+        let Some(cell) = self.cell_index(x, y) else { return };
+        for &index in &self.cells[cell] {
+            let [p1, p2] = self.segments[index];
+            let delta = to_segment(p1, p2, point);
+            let distance = delta.len();
+            if best.as_ref().map_or(true, |best| distance < best.distance) {
+                *best = Some(NearestSegment {
+                    index,
+                    delta,
+                    distance,
+                });
+            }
+        }
+    }
+}
+
+#[test]
+fn test_segment_grid() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    let segments: Vec<Segment> = (0..200)
+        .map(|_| {
+            let p = vec2(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0));
+            [
+                p,
+                p + vec2(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)),
+            ]
+        })
+        .collect();
+    let grid = SegmentGrid::new(segments.clone(), SegmentGrid::DEFAULT_CELL_SIZE);
+    let check = |point: Vec2<f32>| {
+        let expected = segments
+            .iter()
+            .map(|&[p1, p2]| to_segment(p1, p2, point).len())
+            .min_by_key(|&d| r32(d))
+            .unwrap();
+        let nearest = grid.nearest(point).unwrap();
+        assert!((nearest.distance - expected).abs() < 1e-4);
+    };
+    for _ in 0..100 {
+        check(vec2(rng.gen_range(-80.0..80.0), rng.gen_range(-80.0..80.0)));
+    }
+    // Far out in the void
+    check(vec2(1000.0, -700.0));
+}
@@ -2,16 +2,16 @@ use super::*;
 
 impl Game {
     pub fn draw_shops(&self, framebuffer: &mut ugli::Framebuffer) {
-        for &pos in &self.assets.config.fish_shops {
+        for &pos in &self.world.config.fish_shops {
             self.draw_texture(
                 framebuffer,
-                pos.extend(Map::get().get_height(pos)),
+                pos.extend(self.world.map.get_height(pos)),
                 1.0,
                 &self.assets.shops.fish,
                 vec2(0.0, -1.0),
             );
         }
-        for (index, boat_type) in self.assets.config.boat_types.iter().enumerate() {
+        for (index, boat_type) in self.world.config.boat_types.iter().enumerate() {
             let texture = [
                 &self.assets.shops.itsboats,
                 &self.assets.shops.big_boat_shop,
@@ -20,7 +20,7 @@ impl Game {
             for &pos in &boat_type.shops {
                 self.draw_texture(
                     framebuffer,
-                    pos.extend(Map::get().get_height(pos)),
+                    pos.extend(self.world.map.get_height(pos)),
                     1.0,
                     texture,
                     vec2(0.0, -1.0),
@@ -30,26 +30,94 @@ impl Game {
     }
 
     pub fn can_sell_fish(&self) -> bool {
-        let fishing_shop_distance = self
-            .assets
+        self.world
             .config
             .fish_shops
             .iter()
-            .map(|&pos| r32((pos - self.player.pos.pos).len()))
-            .min()
-            .unwrap()
-            .raw();
-        fishing_shop_distance < SHOPPING_DISTANCE
+            .any(|&pos| (pos - self.player.pos.pos).len() < SHOPPING_DISTANCE)
+    }
+
+    // Number keys buy bait, R buys a better rod, Q takes bounties and F shows the fishing reports
+    // at a fish shop, B switches between the baits the player has
+    pub fn shop_key(&mut self, key: geng::Key) {
+        let number_keys = [
+            geng::Key::Num1,
+            geng::Key::Num2,
+            geng::Key::Num3,
+            geng::Key::Num4,
+            geng::Key::Num5,
+        ];
+        if let Some(bait) = number_keys.iter().position(|&number| number == key) {
+            if bait < self.world.config.baits.len() && self.can_sell_fish() {
+                // Make sure the server knows where we are standing
+                self.model.send(Message::Update(self.player.clone()));
+                self.model.send(Message::BuyBait { bait });
+            }
+        }
+        if key == geng::Key::R && self.can_sell_fish() {
+            if let Some((level, _)) = self.next_rod() {
+                self.model.send(Message::Update(self.player.clone()));
+                self.model.send(Message::BuyRod { level });
+            }
+        }
+        if key == geng::Key::Q {
+            self.quest_key();
+        }
+        if key == geng::Key::F && self.can_sell_fish() {
+            self.show_reports = !self.show_reports;
+        }
+        if key == geng::Key::B {
+            let owned: Vec<usize> = (0..self.player.baits.len())
+                .filter(|&bait| self.player.baits[bait] > 0)
+                .collect();
+            let next = match self.player.active_bait() {
+                Some(bait) => owned.iter().copied().find(|&other| other > bait),
+                None => owned.first().copied(),
+            };
+            self.player.selected_bait = next;
+            self.tutorial = match next {
+                Some(bait) => format!("fishing with {}", self.world.config.baits[bait].name),
+                None => "fishing without bait".to_owned(),
+            };
+            self.tutorial_timer = 2.0;
+        }
+    }
+
+    fn next_rod(&self) -> Option<(u8, &RodConfig)> {
+        let level = self.player.rod_level + 1;
+        let rod = self.world.config.rod_types.get(level as usize)?;
+        Some((level, rod))
+    }
+
+    pub fn fish_shop_text(&self) -> String {
+        if self.show_reports {
+            return self.reports_text() + "press F to go back to the shop\n";
+        }
+        let mut text = self.bounty_text();
+        if let Some((_, rod)) = self.next_rod() {
+            text += &format!("press R to buy a {} for ${}\n", rod.name, rod.cost);
+        }
+        for (index, bait) in self.world.config.baits.iter().enumerate() {
+            text += &format!(
+                "press {} to buy {} {} for ${}\n",
+                index + 1,
+                bait.amount,
+                bait.name,
+                bait.cost,
+            );
+        }
+        text += "press F for the fishing reports\n";
+        text
     }
 
     pub fn is_hovering_boat_shop(&self) -> Option<(usize, &BoatConfig)> {
-        for (index, boat_type) in self.assets.config.boat_types.iter().enumerate() {
+        for (index, boat_type) in self.world.config.boat_types.iter().enumerate() {
             let boat_level = index as u8 + 1;
             if let Some(distance) = boat_type
                 .shops
                 .iter()
                 .filter(|&&pos| {
-                    let pos = pos.extend(Map::get().get_height(pos));
+                    let pos = pos.extend(self.world.map.get_height(pos));
                     let ray = self.camera.pixel_ray(
                         self.framebuffer_size,
                         self.geng.window().mouse_pos().map(|x| x as f32),
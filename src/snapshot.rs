@@ -0,0 +1,121 @@
+use super::*;
+
+use anyhow::Context as _;
+
+// Bump this whenever the snapshot format changes
+pub const SNAPSHOT_VERSION: u32 = 1;
+pub const AUTOSAVE_INTERVAL: f32 = 60.0;
+
+fn default_world_name() -> String {
+    DEFAULT_WORLD.to_owned()
+}
+
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+// The persistent part of the world, players are saved separately in their profiles
+#[derive(Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    pub version: u32,
+    #[serde(default = "default_world_name")]
+    pub world: String,
+    pub id_gen: IdGen,
+    pub fishes: Collection<Fish>,
+    pub time: f32,
+    #[serde(default)]
+    pub stocks: Option<FishStocks>,
+}
+
+impl WorldSnapshot {
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read world snapshot {path:?}"))?;
+        let header: SnapshotHeader = serde_json::from_slice(&data)
+            .with_context(|| format!("{path:?} is not a world snapshot"))?;
+        if header.version != SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "World snapshot {path:?} has version {}, but this server only supports version {}",
+                header.version,
+                SNAPSHOT_VERSION,
+            );
+        }
+        serde_json::from_slice(&data)
+            .with_context(|| format!("World snapshot {path:?} is corrupted"))
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let file = std::fs::File::create(&tmp_path)?;
+        serde_json::to_writer(std::io::BufWriter::new(file), self)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+impl Model {
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            version: SNAPSHOT_VERSION,
+            world: self.world.name.clone(),
+            id_gen: self.id_gen.clone(),
+            fishes: self.fishes.0.clone(),
+            time: self.time,
+            stocks: Some(self.stocks.clone()),
+        }
+    }
+
+    pub fn resume(world: WorldRef, snapshot: WorldSnapshot) -> anyhow::Result<Self> {
+        if snapshot.world != world.name {
+            anyhow::bail!(
+                "World snapshot is of world {:?}, but the server runs {:?}",
+                snapshot.world,
+                world.name,
+            );
+        }
+        let species = world.fish_configs.configs.len();
+        if let Some(fish) = snapshot.fishes.iter().find(|fish| fish.index >= species) {
+            anyhow::bail!(
+                "World snapshot has a fish of species {}, but the fish list only has {species}",
+                fish.index,
+            );
+        }
+        let mut model = Self::empty(world);
+        model.id_gen = snapshot.id_gen;
+        model.fishes = SyncCollection(snapshot.fishes);
+        model.time = snapshot.time;
+        // The grid is rebuilt if the cell size changed since
+        if let Some(stocks) = snapshot.stocks {
+            if stocks.cells.len() == model.stocks.cells.len() {
+                model.stocks = stocks;
+            }
+        }
+        *model.weather_until = model.time + model.world.config.weather.min_duration;
+        model.post_bounties();
+        Ok(model)
+    }
+
+    pub fn autosave(&self) {
+        let Some(path) = &*self.autosave_path else { return };
+        match self.snapshot().save(path) {
+            Ok(()) => info!("Saved the world to {path:?}"),
+            Err(e) => error!("Failed to save the world to {path:?}: {e}"),
+        }
+    }
+}
+
+#[test]
+fn test_resume() {
+    let mut model = Model::seeded(WorldRef::default_world(), 1);
+    model.time = 1000.0;
+    let resumed = Model::resume(model.world.clone(), model.snapshot()).unwrap();
+    assert!(!resumed.bounties.is_empty());
+    assert!(*resumed.weather_until > resumed.time);
+
+    let mut snapshot = model.snapshot();
+    let mut fish = snapshot.fishes.iter().next().unwrap().clone();
+    fish.index = model.world.fish_configs.configs.len();
+    snapshot.fishes.insert(fish);
+    assert!(Model::resume(model.world.clone(), snapshot).is_err());
+}
@@ -1,13 +1,10 @@
 use super::*;
 
 impl Game {
-    pub fn play_sound_for_everyone(&self, pos: Vec2<f32>, sound_type: SoundType) {
-        self.play_sound(pos, sound_type);
-        self.model.send(Message::Broadcast(Event::Sound {
-            player: self.player_id,
-            sound_type,
-            pos,
-        }));
+    pub fn play_sound_for_everyone(&self, pos: Vec2<f32>, sound: ClientSound) {
+        self.play_sound(pos, sound.into());
+        self.model
+            .send(Message::Emit(ClientEvent::Sound { sound, pos }));
     }
 
     pub fn play_sound(&self, pos: Vec2<f32>, sound_type: SoundType) {
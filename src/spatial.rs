@@ -0,0 +1,62 @@
+use super::*;
+
+// Uniform grid for neighbour queries, rebuilt every tick
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn build(cell_size: f32, points: impl IntoIterator<Item = Vec2<f32>>) -> Self {
+        let mut result = Self::new(cell_size);
+        for (index, pos) in points.into_iter().enumerate() {
+            result.insert(index, pos);
+        }
+        result
+    }
+
+    fn cell(&self, pos: Vec2<f32>) -> (i32, i32) {
+        let cell = pos.map(|x| (x / self.cell_size).floor() as i32);
+        (cell.x, cell.y)
+    }
+
+    pub fn insert(&mut self, index: usize, pos: Vec2<f32>) {
+        let cell = self.cell(pos);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    // Callers still need to check the exact distance
+    pub fn query(&self, pos: Vec2<f32>, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let (min_x, min_y) = self.cell(pos - vec2(radius, radius));
+        let (max_x, max_y) = self.cell(pos + vec2(radius, radius));
+        (min_x..=max_x)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
+
+#[test]
+fn test_spatial_hash() {
+    let points = [
+        vec2(0.0, 0.0),
+        vec2(2.5, 0.0),
+        vec2(-10.0, 3.0),
+        vec2(0.5, -0.5),
+    ];
+    let hash = SpatialHash::build(3.0, points);
+    let mut found: Vec<usize> = hash
+        .query(vec2(0.0, 0.0), 3.0)
+        .filter(|&i| points[i].len() < 3.0)
+        .collect();
+    found.sort();
+    assert_eq!(found, vec![0, 1, 3]);
+}
@@ -0,0 +1,199 @@
+use super::*;
+
+// The grid is only written to every this many seconds, so it is not resent to the clients every tick
+const REGROWTH_INTERVAL: f32 = 5.0;
+// Cells below this are listed in the reports
+const REPORTED_STOCK: f32 = 0.8;
+const MAX_REPORTED_CELLS: usize = 5;
+// Tries to find a well stocked spot for a new fish before giving up and taking the last one
+pub const SPAWN_TRIES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StockConfig {
+    // Side of the square cells the map is split into
+    pub cell_size: f32,
+    // How much of the local stock a single catch takes
+    pub catch_impact: f32,
+    // How much of the stock grows back per second
+    pub regrowth: f32,
+}
+
+impl Default for StockConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 20.0,
+            catch_impact: 0.1,
+            regrowth: 0.002,
+        }
+    }
+}
+
+// How much fish is left around the map, from 0 when fished out to 1 when untouched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FishStocks {
+    pub width: usize,
+    pub cells: Vec<f32>,
+}
+
+impl FishStocks {
+    pub fn new(world: &World) -> Self {
+        let width = (world.map.size * 2.0 / world.config.stocks.cell_size)
+            .ceil()
+            .max(1.0) as usize;
+        Self {
+            width,
+            cells: vec![1.0; width * width],
+        }
+    }
+
+    fn cell_size(&self, world: &World) -> f32 {
+        world.map.size * 2.0 / self.width as f32
+    }
+
+    fn cell(&self, world: &World, pos: Vec2<f32>) -> usize {
+        let cell = ((pos + vec2(world.map.size, world.map.size)) / self.cell_size(world))
+            .map(|x| (x.floor().max(0.0) as usize).min(self.width - 1));
+        cell.y * self.width + cell.x
+    }
+
+    fn center(&self, world: &World, cell: usize) -> Vec2<f32> {
+        let cell = vec2(cell % self.width, cell / self.width).map(|x| x as f32 + 0.5);
+        cell * self.cell_size(world) - vec2(world.map.size, world.map.size)
+    }
+
+    pub fn get(&self, world: &World, pos: Vec2<f32>) -> f32 {
+        self.cells[self.cell(world, pos)]
+    }
+
+    pub fn deplete(&mut self, world: &World, pos: Vec2<f32>) {
+        let cell = self.cell(world, pos);
+        self.cells[cell] = (self.cells[cell] - world.config.stocks.catch_impact).max(0.0);
+    }
+
+    pub fn regrow(&mut self, amount: f32) {
+        for stock in &mut self.cells {
+            *stock = (*stock + amount).min(1.0);
+        }
+    }
+
+    // Average over the cells that have water in them
+    pub fn overall(&self, world: &World) -> f32 {
+        let stocks: Vec<f32> = (0..self.cells.len())
+            .filter(|&cell| world.map.is_water(self.center(world, cell)))
+            .map(|cell| self.cells[cell])
+            .collect();
+        if stocks.is_empty() {
+            return 1.0;
+        }
+        stocks.iter().sum::<f32>() / stocks.len() as f32
+    }
+
+    // Centers of the most fished out cells with their stock
+    pub fn depleted(&self, world: &World) -> Vec<(Vec2<f32>, f32)> {
+        let mut cells: Vec<(Vec2<f32>, f32)> = (0..self.cells.len())
+            .filter(|&cell| self.cells[cell] < REPORTED_STOCK)
+            .map(|cell| (self.center(world, cell), self.cells[cell]))
+            .collect();
+        cells.sort_by(|a, b| a.1.total_cmp(&b.1));
+        cells.truncate(MAX_REPORTED_CELLS);
+        cells
+    }
+}
+
+fn compass(direction: Vec2<f32>) -> &'static str {
+    const DIRECTIONS: [&str; 8] = [
+        "east",
+        "north east",
+        "north",
+        "north west",
+        "west",
+        "south west",
+        "south",
+        "south east",
+    ];
+    let sector = (direction.arg() / (f32::PI / 4.0)).round() as i32;
+    DIRECTIONS[sector.rem_euclid(8) as usize]
+}
+
+impl Model {
+    pub fn update_stocks(&mut self, delta_time: f32) {
+        if self.every(REGROWTH_INTERVAL, delta_time) {
+            let regrowth = self.world.config.stocks.regrowth;
+            self.stocks.regrow(regrowth * REGROWTH_INTERVAL);
+        }
+    }
+
+    pub fn stock(&self, pos: Vec2<f32>) -> f32 {
+        self.stocks.get(&self.world, pos)
+    }
+}
+
+impl Game {
+    // What the fish shop knows about the fished out areas
+    pub fn reports_text(&self) -> String {
+        let model = self.model.get();
+        let mut text = format!(
+            "fishing reports: the sea is {:.0}% stocked\n",
+            model.stocks.overall(&self.world) * 100.0,
+        );
+        let depleted = model.stocks.depleted(&self.world);
+        if depleted.is_empty() {
+            text += "the fish are plentiful everywhere\n";
+        }
+        for (pos, stock) in depleted {
+            let offset = pos - self.player.pos.pos;
+            text += &format!(
+                "{:.0}m {}: {:.0}% stocked\n",
+                offset.len(),
+                compass(offset),
+                stock * 100.0,
+            );
+        }
+        text
+    }
+}
+
+#[test]
+fn test_deplete_stocks() {
+    let mut model = Model::seeded(WorldRef::default_world(), 13);
+    let pos = vec2(10.0, 10.0);
+    for _ in 0..5 {
+        model.stocks.deplete(&model.world, pos);
+    }
+    let impact = model.world.config.stocks.catch_impact;
+    assert!((model.stock(pos) - (1.0 - 5.0 * impact)).abs() < 1e-5);
+    assert_eq!(model.stock(-pos), 1.0);
+    assert_eq!(model.stocks.depleted(&model.world).len(), 1);
+}
+
+#[test]
+fn test_stocks_regrow() {
+    let mut model = Model::seeded(WorldRef::default_world(), 13);
+    let pos = vec2(10.0, 10.0);
+    model.stocks.deplete(&model.world, pos);
+    for _ in 0..10_000 {
+        model.time += 0.1;
+        model.update_stocks(0.1);
+    }
+    assert_eq!(model.stock(pos), 1.0);
+}
+
+#[test]
+fn test_stock_bites() {
+    let config = BiteConfig::default();
+    let modifiers = |stock| BiteModifiers {
+        waited: 0.0,
+        bait: None,
+        hour: 12.0,
+        weather: Weather::Clear,
+        stock,
+    };
+    assert!(config.rate(&modifiers(0.5)) < config.rate(&modifiers(1.0)));
+}
+
+#[test]
+fn test_compass() {
+    assert_eq!(compass(vec2(0.0, 1.0)), "north");
+    assert_eq!(compass(vec2(-1.0, -1.0)), "south west");
+}
@@ -0,0 +1,136 @@
+use super::*;
+
+// A Collection that is sent to the clients as per-entity changes instead of a full copy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SyncCollection<T: HasId>(pub Collection<T>);
+
+impl<T: HasId> SyncCollection<T> {
+    pub fn new() -> Self {
+        Self(Collection::new())
+    }
+}
+
+impl<T: HasId> Default for SyncCollection<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: HasId> std::ops::Deref for SyncCollection<T> {
+    type Target = Collection<T>;
+    fn deref(&self) -> &Collection<T> {
+        &self.0
+    }
+}
+
+impl<T: HasId> std::ops::DerefMut for SyncCollection<T> {
+    fn deref_mut(&mut self) -> &mut Collection<T> {
+        &mut self.0
+    }
+}
+
+impl<'a, T: HasId> IntoIterator for &'a SyncCollection<T> {
+    type Item = &'a T;
+    type IntoIter = <&'a Collection<T> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.0).into_iter()
+    }
+}
+
+impl<'a, T: HasId> IntoIterator for &'a mut SyncCollection<T> {
+    type Item = &'a mut T;
+    type IntoIter = <&'a mut Collection<T> as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        (&mut self.0).into_iter()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize, T::Id: Serialize, T::Delta: Serialize",
+    deserialize = "T: Deserialize<'de>, T::Id: Deserialize<'de>, T::Delta: Deserialize<'de>",
+))]
+pub struct CollectionDelta<T: HasId + Diff> {
+    pub inserted: Vec<T>,
+    pub removed: Vec<T::Id>,
+    pub changed: Vec<(T::Id, T::Delta)>,
+}
+
+impl<T> Diff for SyncCollection<T>
+where
+    T: HasId + Diff,
+    T::Id: Debug + Clone + Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static,
+{
+    type Delta = CollectionDelta<T>;
+
+    fn diff(&self, to: &Self) -> Self::Delta {
+        let mut delta = CollectionDelta {
+            inserted: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for entity in to {
+            match self.get(entity.id()) {
+                Some(old) => {
+                    if old != entity {
+                        delta.changed.push((entity.id().clone(), old.diff(entity)));
+                    }
+                }
+                None => delta.inserted.push(entity.clone()),
+            }
+        }
+        for entity in self {
+            if to.get(entity.id()).is_none() {
+                delta.removed.push(entity.id().clone());
+            }
+        }
+        delta
+    }
+
+    fn update(&mut self, delta: &Self::Delta) {
+        for id in &delta.removed {
+            self.remove(id);
+        }
+        for entity in &delta.inserted {
+            self.insert(entity.clone());
+        }
+        for (id, entity_delta) in &delta.changed {
+            if let Some(entity) = self.get_mut(id) {
+                entity.update(entity_delta);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_collection_delta() {
+    let mut model = Model::init();
+    let before = model.clone();
+    let removed = model.fishes.iter().next().unwrap().id;
+    model.fishes.remove(&removed);
+    model.spawn_fish(0);
+    <Model as simple_net::Model>::tick(&mut model, &mut Vec::new());
+
+    let fishes_delta = before.fishes.diff(&model.fishes);
+    assert_eq!(fishes_delta.removed, vec![removed]);
+    assert_eq!(fishes_delta.inserted.len(), 1);
+
+    let delta = before.diff(&model);
+    let mut updated = before.clone();
+    updated.update(&delta);
+    assert_eq!(updated.fishes.len(), model.fishes.len());
+    for fish in &model.fishes {
+        let synced = updated.fishes.get(&fish.id).unwrap();
+        assert!((synced.pos.pos - fish.pos.pos).len() < 0.1);
+    }
+
+    let delta_size = serde_json::to_vec(&delta).unwrap().len();
+    let full_size = serde_json::to_vec(&(&model.players, &model.fishes))
+        .unwrap()
+        .len();
+    assert!(delta_size < full_size);
+    let pos = &model.fishes.iter().next().unwrap().pos;
+    let quantized_size = serde_json::to_vec(&pos.diff(pos)).unwrap().len();
+    assert!(quantized_size < serde_json::to_vec(pos).unwrap().len());
+}
@@ -17,3 +17,74 @@ pub fn make_repeated(texture: &mut ugli::Texture) {
 pub fn make_looped(sound: &mut geng::Sound) {
     sound.looped = true;
 }
+
+// Stored in the model but never sent to the clients
+#[derive(Debug, Clone, Default)]
+pub struct ServerOnly<T>(pub T);
+
+impl<T> std::ops::Deref for ServerOnly<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for ServerOnly<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> PartialEq for ServerOnly<T> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Serialize for ServerOnly<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de, T: Default> Deserialize<'de> for ServerOnly<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(Self::default())
+    }
+}
+
+impl<T: Debug + Clone + Default + Send + Sync + 'static> Diff for ServerOnly<T> {
+    type Delta = ();
+    fn diff(&self, _to: &Self) -> Self::Delta {}
+    fn update(&mut self, _delta: &Self::Delta) {}
+}
+
+// The server's random number generator, seeded from entropy unless a test needs repeatable ticks
+#[derive(Debug, Clone)]
+pub struct ModelRng(StdRng);
+
+impl ModelRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for ModelRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+impl std::ops::Deref for ModelRng {
+    type Target = StdRng;
+    fn deref(&self) -> &StdRng {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for ModelRng {
+    fn deref_mut(&mut self) -> &mut StdRng {
+        &mut self.0
+    }
+}
@@ -0,0 +1,465 @@
+use super::*;
+
+pub const MAX_NAME_LEN: usize = 15;
+// Casting onto land or into space catches any fish this close to the bobber
+pub const BOBBER_CATCH_RADIUS: f32 = 1.0;
+// Fish keep moving while the catch is on its way to the server
+const CATCH_RADIUS_TOLERANCE: f32 = 0.5;
+// Even the weakest fish fights for a while after biting
+const MIN_STRUGGLE_TIME: f32 = 0.5;
+
+// Extra slack on top of the max speed to account for network jitter
+const SPEED_TOLERANCE: f32 = 1.5;
+// Getting in and out of boats moves the player instantly
+const MAX_JUMP_DISTANCE: f32 = 4.0;
+const JUMP_COOLDOWN: f32 = 1.0;
+// Every player can send this many events per second on average
+const CLIENT_EVENT_RATE: f32 = 4.0;
+const CLIENT_EVENT_BURST: f32 = 8.0;
+
+// The only events clients are allowed to send to everyone else
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ClientEvent {
+    Sound { sound: ClientSound, pos: Vec2<f32> },
+}
+
+// Sounds of the player's own actions, everything else is played by the server
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClientSound {
+    Casting,
+    Ding,
+    EnterBoat,
+    ExitBoat,
+    ShowFish,
+    Splash,
+    StopFishing,
+    Whip,
+}
+
+impl From<ClientSound> for SoundType {
+    fn from(sound: ClientSound) -> Self {
+        match sound {
+            ClientSound::Casting => Self::Casting,
+            ClientSound::Ding => Self::Ding,
+            ClientSound::EnterBoat => Self::EnterBoat,
+            ClientSound::ExitBoat => Self::ExitBoat,
+            ClientSound::ShowFish => Self::ShowFish,
+            ClientSound::Splash => Self::Splash,
+            ClientSound::StopFishing => Self::StopFishing,
+            ClientSound::Whip => Self::Whip,
+        }
+    }
+}
+
+// Token bucket limiting how often a player can send events
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    tokens: f32,
+    last_time: f32,
+}
+
+impl RateLimit {
+    pub fn new(time: f32) -> Self {
+        Self {
+            tokens: CLIENT_EVENT_BURST,
+            last_time: time,
+        }
+    }
+
+    pub fn try_take(&mut self, time: f32) -> bool {
+        self.tokens =
+            (self.tokens + (time - self.last_time) * CLIENT_EVENT_RATE).min(CLIENT_EVENT_BURST);
+        self.last_time = time;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+// Where the server last let the player be, more updates don't buy extra distance
+#[derive(Debug, Clone, Copy)]
+pub struct MoveAnchor {
+    pub time: f32,
+    pub pos: Vec2<f32>,
+    pub last_jump: f32,
+}
+
+// The struggle as last accepted by the server, so the fish can't be tired out faster than the rod allows
+#[derive(Debug, Clone, Copy)]
+pub struct StruggleCheck {
+    pub started: f32,
+    pub time: f32,
+    pub stamina: f32,
+    pub bobber_pos: Vec2<f32>,
+}
+
+impl StruggleCheck {
+    pub fn new(time: f32, bobber_pos: Vec2<f32>) -> Self {
+        Self {
+            started: time,
+            time,
+            stamina: 1.0,
+            bobber_pos,
+        }
+    }
+}
+
+enum Verdict {
+    Accept,
+    // The client has not heard about a server-side change yet
+    Ignore,
+    Reject,
+}
+
+impl Model {
+    // Turns an event from a client into one for everyone, stamped with the real sender
+    pub fn accept_client_event(&mut self, player_id: Id, event: ClientEvent) -> Option<Event> {
+        let player = self.players.get(&player_id)?;
+        let time = self.time;
+        let limit = self
+            .client_event_limits
+            .entry(player_id)
+            .or_insert_with(|| RateLimit::new(time));
+        if !limit.try_take(time) {
+            return None;
+        }
+        match event {
+            ClientEvent::Sound { sound, pos } => {
+                let reach = self.world.config.rod(player.rod_level).line_length + MAX_JUMP_DISTANCE;
+                if (pos - player.pos.pos).len() > reach {
+                    return None;
+                }
+                Some(Event::Sound {
+                    player: Some(player_id),
+                    sound_type: sound.into(),
+                    pos,
+                })
+            }
+        }
+    }
+
+    fn validate_fishing_state(&self, player: &Player, new: &FishingState) -> Verdict {
+        let old = &player.fishing_state;
+        if old == new {
+            return Verdict::Accept;
+        }
+        match (old, new) {
+            (_, FishingState::Idle) => Verdict::Accept,
+            (FishingState::Idle, FishingState::Spinning) => Verdict::Accept,
+            (FishingState::Idle | FishingState::Spinning, FishingState::Casting(target)) => {
+                let line_length = self.world.config.rod(player.rod_level).line_length;
+                if (*target - player.pos.pos).len() <= line_length {
+                    Verdict::Accept
+                } else {
+                    Verdict::Reject
+                }
+            }
+            (FishingState::Casting(target), FishingState::Waiting(bobber_pos)) => {
+                if target == bobber_pos {
+                    Verdict::Accept
+                } else {
+                    Verdict::Reject
+                }
+            }
+            (
+                FishingState::PreReeling {
+                    bobber_pos: target, ..
+                },
+                FishingState::Waiting(bobber_pos),
+            ) => {
+                if target == bobber_pos {
+                    Verdict::Ignore
+                } else {
+                    Verdict::Reject
+                }
+            }
+            (FishingState::PreReeling { fish, .. }, FishingState::Struggling(struggle)) => {
+                if *fish == struggle.fish && self.valid_struggle(player, struggle) {
+                    Verdict::Accept
+                } else {
+                    Verdict::Reject
+                }
+            }
+            (FishingState::Struggling(old), FishingState::Struggling(new)) => {
+                // Fish never get their strength back
+                if old.fish == new.fish
+                    && new.stamina <= old.stamina
+                    && new.time >= old.time
+                    && self.valid_struggle(player, new)
+                {
+                    Verdict::Accept
+                } else {
+                    Verdict::Reject
+                }
+            }
+            (FishingState::Casting(_), FishingState::Attached(id)) => {
+                if *id != player.id && self.players.get(id).is_some() {
+                    Verdict::Accept
+                } else {
+                    Verdict::Reject
+                }
+            }
+            _ => Verdict::Reject,
+        }
+    }
+
+    fn valid_struggle(&self, player: &Player, struggle: &Struggle) -> bool {
+        let Some(fish) = self.fishes.get(&struggle.fish) else { return false };
+        let Some(check) = self.struggle_checks.get(&player.id) else { return false };
+        let rod = self.world.config.rod(player.rod_level);
+        let config = &self.world.fish_configs.configs[fish.index].struggle;
+        let elapsed =
+            (self.time - check.time).max(1.0 / <Self as simple_net::Model>::TICKS_PER_SECOND);
+        let max_tiring = elapsed / config.stamina * SPEED_TOLERANCE;
+        let max_speed = REEL_SPEED * rod.reel_strength + MAX_PULL * config.strength;
+        (0.0..1.0).contains(&struggle.tension)
+            && (0.0..=1.0).contains(&struggle.stamina)
+            && check.stamina - struggle.stamina <= max_tiring
+            && (struggle.bobber_pos - check.bobber_pos).len()
+                <= max_speed * elapsed * SPEED_TOLERANCE
+            && (struggle.bobber_pos - player.pos.pos).len() <= rod.line_length + MAX_JUMP_DISTANCE
+    }
+
+    // Lost the boat they were sitting in, or drifted off into the void without an airship
+    fn is_stranded(&self, player: &Player) -> bool {
+        let lost_ride = player
+            .seated
+            .map_or(false, |seated| self.players.get(&seated.player).is_none());
+        let lost_in_void = player.seated.is_none()
+            && player.boat_level < 3
+            && self.world.map.get_is_void(player.pos.pos);
+        lost_ride || lost_in_void
+    }
+
+    // Checks that the player was actually able to catch this fish
+    pub fn validate_catch(&self, player_id: Id, fish_id: Id) -> bool {
+        let Some(player) = self.players.get(&player_id) else { return false };
+        let Some(fish) = self.fishes.get(&fish_id) else { return false };
+        match player.fishing_state {
+            FishingState::Struggling(struggle) => {
+                let Some(check) = self.struggle_checks.get(&player_id) else { return false };
+                struggle.fish == fish_id
+                    && self.time - check.started >= MIN_STRUGGLE_TIME
+                    && (struggle.bobber_pos - player.pos.pos).len()
+                        < LANDING_DISTANCE + CATCH_RADIUS_TOLERANCE
+            }
+            FishingState::Casting(bobber_pos) => {
+                !self.world.map.is_water(bobber_pos)
+                    && (fish.pos.pos - bobber_pos).len()
+                        < BOBBER_CATCH_RADIUS + CATCH_RADIUS_TOLERANCE
+            }
+            _ => false,
+        }
+    }
+
+    // Returns false if the client needs a correction
+    pub fn accept_update(&mut self, player_id: Id, mut data: Player) -> bool {
+        let Some(player) = self.players.get(&player_id) else { return true };
+        let mut valid = true;
+
+        // Money, boats and fish are owned by the server
+        if data.boat_level > player.boat_level || data.rod_level > player.rod_level {
+            valid = false;
+        }
+        data.boat_level = player.boat_level;
+        data.rod_level = player.rod_level;
+        data.money = player.money;
+        data.inventory = player.inventory.clone();
+        data.fishdex = player.fishdex.clone();
+        data.baits = player.baits.clone();
+        data.quests = player.quests.clone();
+        data.achievements = player.achievements.clone();
+        if let Some(bait) = data.selected_bait {
+            if bait >= self.world.config.baits.len() {
+                data.selected_bait = None;
+                valid = false;
+            }
+        }
+        if let Some(fish) = data.fish_in_hands {
+            if !data.inventory.iter().any(|caught| caught.fish_type == fish) {
+                data.fish_in_hands = None;
+            }
+        }
+
+        if data.name.chars().count() > MAX_NAME_LEN {
+            data.name = data.name.chars().take(MAX_NAME_LEN).collect();
+            valid = false;
+        }
+
+        if let Some(seated) = data.seated {
+            if seated.player == player_id || self.players.get(&seated.player).is_none() {
+                data.seated = None;
+                valid = false;
+            }
+        }
+
+        match self.validate_fishing_state(player, &data.fishing_state) {
+            Verdict::Accept => {
+                if let FishingState::Struggling(struggle) = data.fishing_state {
+                    // Like the position, only a new tick moves the struggle forward
+                    if let Some(check) = self.struggle_checks.get_mut(&player_id) {
+                        if self.time > check.time {
+                            check.time = self.time;
+                            check.stamina = struggle.stamina;
+                            check.bobber_pos = struggle.bobber_pos;
+                        }
+                    }
+                }
+            }
+            Verdict::Ignore => data.fishing_state = player.fishing_state.clone(),
+            Verdict::Reject => {
+                data.fishing_state = player.fishing_state.clone();
+                valid = false;
+            }
+        }
+
+        let anchor = *self.move_anchors.entry(player_id).or_insert(MoveAnchor {
+            time: self.time,
+            pos: player.pos.pos,
+            last_jump: f32::NEG_INFINITY,
+        });
+        let elapsed =
+            (self.time - anchor.time).max(1.0 / <Self as simple_net::Model>::TICKS_PER_SECOND);
+        let in_water = |pos| self.world.map.get_height(pos) < SHORE_HEIGHT;
+        // The wind pushes boats on top of their own speed
+        let max_speed =
+            MovementProps::player(in_water(anchor.pos)).max_speed + self.weather.max_wind_speed();
+        let mut max_distance = max_speed * elapsed * SPEED_TOLERANCE;
+        let jumped = data.seated != player.seated || in_water(data.pos.pos) != in_water(anchor.pos);
+        if jumped && self.time - anchor.last_jump >= JUMP_COOLDOWN {
+            max_distance += MAX_JUMP_DISTANCE;
+        }
+        let rescued = data.pos.pos == Vec2::ZERO && self.is_stranded(player);
+        if rescued || (data.pos.pos - anchor.pos).len() <= max_distance {
+            // Later updates within the same tick are measured from the same anchor
+            if self.time > anchor.time || rescued {
+                let last_jump = if jumped { self.time } else { anchor.last_jump };
+                self.move_anchors.insert(
+                    player_id,
+                    MoveAnchor {
+                        time: self.time,
+                        pos: data.pos.pos,
+                        last_jump,
+                    },
+                );
+            }
+        } else {
+            data.pos = player.pos.clone();
+            valid = false;
+        }
+
+        *self.players.get_mut(&player_id).unwrap() = data;
+        valid
+    }
+}
+
+#[test]
+fn test_update_spam() {
+    let mut model = Model::init();
+    let player_id = model.id_gen.gen();
+    model.players.insert(Player::new(player_id, Vec2::ZERO));
+    let mut moved = 0;
+    for i in 1..=100 {
+        let mut data = model.players.get(&player_id).unwrap().clone();
+        data.pos.pos = vec2(0.1 * i as f32, 0.0);
+        if model.accept_update(player_id, data) {
+            moved += 1;
+        }
+    }
+    // One tick only buys one tick worth of movement, however many updates are sent
+    assert!(moved < 5);
+
+    // The spawn is only a way out for players that are stuck
+    let far_id = model.id_gen.gen();
+    model.players.insert(Player::new(far_id, vec2(30.0, 0.0)));
+    let mut data = model.players.get(&far_id).unwrap().clone();
+    data.pos.pos = Vec2::ZERO;
+    assert!(!model.accept_update(far_id, data));
+}
+
+#[test]
+fn test_client_events() {
+    let mut model = Model::init();
+    let player_id = model.id_gen.gen();
+    model.players.insert(Player::new(player_id, Vec2::ZERO));
+    let sound = |sound, pos| ClientEvent::Sound { sound, pos };
+    // Sounds of the server can't even be sent
+    assert!(serde_json::from_str::<ClientSound>("\"SellFish\"").is_err());
+    assert!(model
+        .accept_client_event(player_id, sound(ClientSound::Whip, vec2(100.0, 0.0)))
+        .is_none());
+    let event = model.accept_client_event(player_id, sound(ClientSound::Splash, Vec2::ZERO));
+    assert!(matches!(
+        event,
+        Some(Event::Sound {
+            player: Some(player),
+            sound_type: SoundType::Splash,
+            ..
+        }) if player == player_id
+    ));
+    let accepted = (0..100)
+        .filter(|_| {
+            model
+                .accept_client_event(player_id, sound(ClientSound::Whip, Vec2::ZERO))
+                .is_some()
+        })
+        .count();
+    assert!(accepted < 10);
+    model.time += 10.0;
+    assert!(model
+        .accept_client_event(player_id, sound(ClientSound::Whip, Vec2::ZERO))
+        .is_some());
+}
+
+#[test]
+fn test_struggle_speed() {
+    let mut model = Model::init();
+    let player_id = model.id_gen.gen();
+    model.players.insert(Player::new(player_id, Vec2::ZERO));
+    let fish_id = model.id_gen.gen();
+    let bobber_pos = vec2(5.0, 0.0);
+    model.fishes.insert(Fish::new(fish_id, 0, bobber_pos));
+    model.players.get_mut(&player_id).unwrap().fishing_state = FishingState::PreReeling {
+        fish: fish_id,
+        bobber_pos,
+    };
+    model
+        .struggle_checks
+        .insert(player_id, StruggleCheck::new(model.time, bobber_pos));
+    let struggle = |model: &mut Model, stamina, bobber_pos| {
+        let mut data = model.players.get(&player_id).unwrap().clone();
+        data.fishing_state = FishingState::Struggling(Struggle {
+            stamina,
+            ..Struggle::new(fish_id, bobber_pos)
+        });
+        model.accept_update(player_id, data)
+    };
+
+    // Neither tired out nor reeled in within a single tick
+    assert!(!struggle(&mut model, 0.0, bobber_pos));
+    assert!(!struggle(&mut model, 1.0, vec2(0.5, 0.0)));
+    assert!(struggle(&mut model, 1.0, bobber_pos));
+    assert!(!model.validate_catch(player_id, fish_id));
+}
+
+#[test]
+fn test_struggle_duration() {
+    let mut model = Model::init();
+    let player_id = model.id_gen.gen();
+    model.players.insert(Player::new(player_id, Vec2::ZERO));
+    let fish_id = model.id_gen.gen();
+    let bobber_pos = vec2(LANDING_DISTANCE * 0.5, 0.0);
+    model.fishes.insert(Fish::new(fish_id, 0, bobber_pos));
+    model
+        .struggle_checks
+        .insert(player_id, StruggleCheck::new(model.time, bobber_pos));
+    model.players.get_mut(&player_id).unwrap().fishing_state =
+        FishingState::Struggling(Struggle::new(fish_id, bobber_pos));
+
+    // Landing right after the bite means the struggle was skipped
+    assert!(!model.validate_catch(player_id, fish_id));
+    model.time += 1.0;
+    assert!(model.validate_catch(player_id, fish_id));
+}
@@ -0,0 +1,255 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Fog,
+    Storm,
+}
+
+impl Weather {
+    pub const ALL: [Self; 4] = [Self::Clear, Self::Rain, Self::Fog, Self::Storm];
+
+    pub fn default_chances() -> HashMap<Self, f32> {
+        HashMap::from_iter([
+            (Self::Clear, 5.0),
+            (Self::Rain, 3.0),
+            (Self::Fog, 2.0),
+            (Self::Storm, 1.0),
+        ])
+    }
+
+    pub fn bite_multiplier(&self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => 1.5,
+            Self::Fog => 1.2,
+            Self::Storm => 0.5,
+        }
+    }
+
+    // Drift of boats and fish, in units per second
+    pub fn wind(&self, time: f32) -> Vec2<f32> {
+        let (strength, gusts) = match self {
+            Self::Clear | Self::Fog => return Vec2::ZERO,
+            Self::Rain => (0.2, 0.0),
+            Self::Storm => (0.8, 0.5),
+        };
+        let direction = vec2(1.0, 0.0).rotate(time * 0.02);
+        direction * strength * (1.0 + gusts * (time * 1.3).sin())
+    }
+
+    pub fn max_wind_speed(&self) -> f32 {
+        match self {
+            Self::Clear | Self::Fog => 0.0,
+            Self::Rain => 0.2,
+            Self::Storm => 1.2,
+        }
+    }
+
+    // How quickly things fade into the fog with the distance from the camera
+    pub fn fog_density(&self) -> f32 {
+        match self {
+            Self::Clear => 0.0,
+            Self::Rain => 0.015,
+            Self::Fog => 0.06,
+            Self::Storm => 0.03,
+        }
+    }
+
+    // Raindrops per second around the player
+    pub fn rain(&self) -> f32 {
+        match self {
+            Self::Rain => 30.0,
+            Self::Storm => 80.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn forecast(&self) -> &'static str {
+        match self {
+            Self::Clear => "the sky clears up",
+            Self::Rain => "it starts to rain, the fish are biting",
+            Self::Fog => "a fog rolls in",
+            Self::Storm => "a storm is coming, stay away from the deep sea",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherConfig {
+    // Seconds a weather lasts, rolled between the two
+    pub min_duration: f32,
+    pub max_duration: f32,
+    // Relative chances of what the weather turns into next
+    pub chances: HashMap<Weather, f32>,
+    // Chance per second for a wave to wash a fish out of a boat on the deep sea during a storm
+    pub storm_wash_rate: f32,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            min_duration: 120.0,
+            max_duration: 300.0,
+            chances: Weather::default_chances(),
+            storm_wash_rate: 0.02,
+        }
+    }
+}
+
+impl MovementProps {
+    // Boats are harder to steer in bad weather
+    pub fn in_weather(mut self, weather: Weather) -> Self {
+        if self.water {
+            let handling = match weather {
+                Weather::Clear | Weather::Fog => 1.0,
+                Weather::Rain => 0.8,
+                Weather::Storm => 0.5,
+            };
+            self.acceleration *= handling;
+            self.max_rotation_speed *= handling;
+        }
+        self
+    }
+}
+
+impl Model {
+    pub fn update_weather(&mut self, delta_time: f32, events: &mut Vec<Event>) {
+        let config = &self.world.config.weather;
+        if self.time >= *self.weather_until {
+            let current = self.weather;
+            let rng: &mut StdRng = &mut self.rng;
+            let next = Weather::ALL
+                .choose_weighted(&mut *rng, |weather| {
+                    if *weather == current {
+                        0.0
+                    } else {
+                        config.chances.get(weather).copied().unwrap_or(0.0)
+                    }
+                })
+                .copied()
+                .unwrap_or(current);
+            *self.weather_until =
+                self.time + rng.gen_range(config.min_duration..=config.max_duration);
+            if next != current {
+                self.weather = next;
+                events.push(Event::WeatherChanged(next));
+            }
+        }
+
+        if self.weather != Weather::Storm {
+            return;
+        }
+        let chance = (config.storm_wash_rate * delta_time).clamp(0.0, 1.0) as f64;
+        let mut exposed: Vec<Id> = self
+            .players
+            .iter()
+            .filter(|player| {
+                !player.inventory.is_empty()
+                    && self.world.map.get_height(player.pos.pos) < DEEP_SEA_HEIGHT
+            })
+            .map(|player| player.id)
+            .collect();
+        exposed.sort_by_key(|id| id.0);
+        for id in exposed {
+            if !self.rng.gen_bool(chance) {
+                continue;
+            }
+            let player = self.players.get_mut(&id).unwrap();
+            let slot = self.rng.gen_range(0..player.inventory.len());
+            let fish = player.inventory.remove(slot);
+            events.push(Event::WashedOverboard { player: id, fish });
+        }
+    }
+}
+
+impl Game {
+    pub fn update_weather(&mut self, delta_time: f32) {
+        let (weather, time) = {
+            let model = self.model.get();
+            (model.weather, model.time)
+        };
+        self.fog_density +=
+            (weather.fog_density() - self.fog_density).clamp_abs(delta_time * 0.005);
+
+        let height = self.world.map.get_height(self.player.pos.pos);
+        if height < SHORE_HEIGHT && self.player.seated.is_none() {
+            // The wind is much stronger out on the deep sea
+            let exposure = if height < DEEP_SEA_HEIGHT { 1.0 } else { 0.3 };
+            self.player.pos.pos += weather.wind(time) * exposure * delta_time;
+        }
+
+        let drops = weather.rain() * delta_time;
+        let mut count = drops.floor() as usize;
+        if global_rng().gen_bool(drops.fract() as f64) {
+            count += 1;
+        }
+        for _ in 0..count {
+            let offset = vec2(global_rng().gen_range(0.0..15.0), 0.0)
+                .rotate(global_rng().gen_range(0.0..2.0 * f32::PI));
+            let pos = self.player.pos.pos + offset;
+            if self.world.map.is_water(pos) {
+                self.splashes.push(Splash::new(pos, 2, 3.0));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_weather_changes() {
+    let mut model = Model::seeded(WorldRef::default_world(), 7);
+    let mut events = Vec::new();
+    for _ in 0..10_000 {
+        model.time += 1.0;
+        model.update_weather(1.0, &mut events);
+    }
+    let seen: HashSet<Weather> = events
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::WeatherChanged(weather) => Some(weather),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(seen.len(), Weather::ALL.len());
+}
+
+#[test]
+fn test_weather_duration() {
+    let mut model = Model::seeded(WorldRef::default_world(), 7);
+    model.time = *model.weather_until;
+    model.update_weather(0.0, &mut Vec::new());
+    let config = &model.world.config.weather;
+    let duration = *model.weather_until - model.time;
+    assert!((config.min_duration..=config.max_duration).contains(&duration));
+}
+
+#[test]
+fn test_storm_wash() {
+    let mut model = Model::seeded(WorldRef::default_world(), 7);
+    let map = &model.world.map;
+    let deep_sea = (0..100)
+        .flat_map(|x| (0..100).map(move |y| vec2(x, y)))
+        .map(|cell| cell.map(|x| x as f32 / 50.0 - 1.0) * map.size)
+        .find(|&pos| map.get_height(pos) < DEEP_SEA_HEIGHT)
+        .unwrap();
+    let player_id = model.id_gen.gen();
+    let mut player = Player::new(player_id, deep_sea);
+    let caught = CaughtFishInstance::average(0, &model.world.fish_configs.configs[0]);
+    player.inventory = vec![caught];
+    model.players.insert(player);
+    model.weather = Weather::Storm;
+    *model.weather_until = f32::INFINITY;
+
+    // Long enough for the wave to come for sure
+    let mut events = Vec::new();
+    model.update_weather(1e6, &mut events);
+    assert!(model.players.get(&player_id).unwrap().inventory.is_empty());
+    assert!(matches!(
+        events[..],
+        [Event::WashedOverboard { player, .. }] if player == player_id
+    ));
+}
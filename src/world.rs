@@ -0,0 +1,309 @@
+use super::*;
+
+use anyhow::Context as _;
+
+pub const DEFAULT_WORLD: &str = "default";
+
+// Describes a map, paths are relative to the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapManifest {
+    pub name: String,
+    pub heightmap: std::path::PathBuf,
+    pub colormap: std::path::PathBuf,
+    // The map covers `-size..size` on both axes
+    pub size: f32,
+    // Heights of the darkest and the brightest pixel of the heightmap
+    pub deepest_depth: f32,
+    pub highest_land: f32,
+    pub fish_list: std::path::PathBuf,
+    pub fish_shops: Vec<Vec2<f32>>,
+    // Shop locations for every boat type in the config
+    pub boat_shops: Vec<Vec<Vec2<f32>>>,
+}
+
+// Where clients look for the maps that are not bundled with the game
+pub fn map_manifest_path(name: &str) -> std::path::PathBuf {
+    static_path().join("maps").join(name).join("map.json")
+}
+
+// Everything a world is made of that does not change while it runs
+pub struct World {
+    pub name: String,
+    pub map: Map,
+    pub fish_configs: FishConfigs,
+    // Region of every fish type
+    pub regions: Vec<Region>,
+    pub config: Config,
+}
+
+impl World {
+    // The world that ships with the game, described by `static/assets/map.json`
+    pub fn bundled() -> Self {
+        let manifest: MapManifest =
+            serde_json::from_str(include_str!("../static/assets/map.json")).unwrap();
+        let map = Map::from_images(
+            &manifest,
+            include_bytes!("../static/assets/map.png"),
+            include_bytes!("../static/assets/map_color.png"),
+        )
+        .unwrap();
+        let fish_configs = FishConfigs {
+            configs: serde_json::from_str(include_str!("../static/assets/fish/list.json")).unwrap(),
+        };
+        Self::new(manifest, map, fish_configs).unwrap()
+    }
+
+    pub fn load(manifest_path: &std::path::Path) -> anyhow::Result<Self> {
+        let read = |path: &std::path::Path| {
+            std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))
+        };
+        let manifest: MapManifest = serde_json::from_slice(&read(manifest_path)?)
+            .with_context(|| format!("Failed to parse map manifest {manifest_path:?}"))?;
+        let dir = manifest_path.parent().unwrap_or(std::path::Path::new("."));
+        let heightmap = read(&dir.join(&manifest.heightmap))?;
+        let colormap = read(&dir.join(&manifest.colormap))?;
+        let fish_list = read(&dir.join(&manifest.fish_list))?;
+        Self::from_files(manifest, &heightmap, &colormap, &fish_list)
+    }
+
+    // Same as World::load, but through geng so that it works on the web too
+    pub async fn load_async(geng: &Geng, manifest_path: &std::path::Path) -> anyhow::Result<Self> {
+        let manifest = <String as geng::LoadAsset>::load(geng, manifest_path).await?;
+        let manifest: MapManifest = serde_json::from_str(&manifest)
+            .with_context(|| format!("Failed to parse map manifest {manifest_path:?}"))?;
+        let dir = manifest_path.parent().unwrap_or(std::path::Path::new("."));
+        let (heightmap, colormap, fish_list) = future::try_join3(
+            <Vec<u8> as geng::LoadAsset>::load(geng, &dir.join(&manifest.heightmap)),
+            <Vec<u8> as geng::LoadAsset>::load(geng, &dir.join(&manifest.colormap)),
+            <String as geng::LoadAsset>::load(geng, &dir.join(&manifest.fish_list)),
+        )
+        .await?;
+        Self::from_files(manifest, &heightmap, &colormap, fish_list.as_bytes())
+    }
+
+    fn from_files(
+        manifest: MapManifest,
+        heightmap: &[u8],
+        colormap: &[u8],
+        fish_list: &[u8],
+    ) -> anyhow::Result<Self> {
+        let map = Map::from_images(&manifest, heightmap, colormap)
+            .with_context(|| format!("Failed to load map {:?}", manifest.name))?;
+        let fish_configs = FishConfigs {
+            configs: serde_json::from_slice(fish_list)
+                .with_context(|| format!("Failed to parse the fish list of {:?}", manifest.name))?,
+        };
+        Self::new(manifest, map, fish_configs)
+    }
+
+    pub fn new(
+        manifest: MapManifest,
+        map: Map,
+        mut fish_configs: FishConfigs,
+    ) -> anyhow::Result<Self> {
+        let mut config: Config =
+            serde_json::from_str(include_str!("../static/assets/config.json")).unwrap();
+        anyhow::ensure!(
+            config.rod_types.len() == ROD_TEXTURES + 1,
+            "There are {} rod types, but {} rod textures",
+            config.rod_types.len(),
+            ROD_TEXTURES,
+        );
+        anyhow::ensure!(
+            config.weather.min_duration <= config.weather.max_duration,
+            "Weather min_duration is longer than max_duration",
+        );
+        if manifest.boat_shops.len() != config.boat_types.len() {
+            anyhow::bail!(
+                "Map {:?} has shops for {} boat types, but there are {}",
+                manifest.name,
+                manifest.boat_shops.len(),
+                config.boat_types.len(),
+            );
+        }
+        for fish in &mut fish_configs.configs {
+            if let Some(region) = &mut fish.spawn_circle.region {
+                region
+                    .prepare(&map)
+                    .with_context(|| format!("Invalid spawn region of {:?}", fish.name))?;
+            }
+        }
+        config.fish_shops = manifest.fish_shops;
+        for (boat_type, shops) in config.boat_types.iter_mut().zip(manifest.boat_shops) {
+            boat_type.shops = shops;
+        }
+        let regions = fish_configs
+            .configs
+            .iter()
+            .map(|fish| Region::classify(&map, &fish.spawn_circle))
+            .collect();
+        Ok(Self {
+            name: manifest.name,
+            map,
+            fish_configs,
+            regions,
+            config,
+        })
+    }
+}
+
+static WORLDS: std::sync::Mutex<Vec<WorldRef>> = std::sync::Mutex::new(Vec::new());
+
+struct WorldSlot {
+    name: String,
+    world: std::sync::OnceLock<World>,
+}
+
+// Serialized as just the name. A world that is not registered yet gets an empty
+// handle, which clients fill in with load before using it
+#[derive(Clone)]
+pub struct WorldRef(std::sync::Arc<WorldSlot>);
+
+impl WorldRef {
+    fn new(name: String, world: Option<World>) -> Self {
+        let slot = WorldSlot {
+            name,
+            world: std::sync::OnceLock::new(),
+        };
+        if let Some(world) = world {
+            let _ = slot.world.set(world);
+        }
+        Self(std::sync::Arc::new(slot))
+    }
+
+    // Makes the world available by its name, replacing any other world with the same name
+    pub fn register(world: World) -> Self {
+        let world = Self::new(world.name.clone(), Some(world));
+        let mut worlds = WORLDS.lock().unwrap();
+        worlds.retain(|other| other.name() != world.name());
+        worlds.push(world.clone());
+        world
+    }
+
+    pub fn find(name: &str) -> Self {
+        let mut worlds = WORLDS.lock().unwrap();
+        if let Some(world) = worlds.iter().find(|world| world.name() == name) {
+            return world.clone();
+        }
+        let world = if name == DEFAULT_WORLD {
+            Self::new(name.to_owned(), Some(World::bundled()))
+        } else {
+            Self::new(name.to_owned(), None)
+        };
+        worlds.push(world.clone());
+        world
+    }
+
+    pub fn default_world() -> Self {
+        Self::find(DEFAULT_WORLD)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.0.world.get().is_some()
+    }
+
+    // Fetches the world from map_manifest_path if only its name is known
+    pub async fn load(&self, geng: &Geng) -> anyhow::Result<()> {
+        if self.is_loaded() {
+            return Ok(());
+        }
+        let path = map_manifest_path(self.name());
+        let world = World::load_async(geng, &path).await?;
+        if world.name != self.name() {
+            anyhow::bail!(
+                "{path:?} is named {:?} instead of {:?}",
+                world.name,
+                self.name()
+            );
+        }
+        let _ = self.0.world.set(world);
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for WorldRef {
+    type Target = World;
+    fn deref(&self) -> &World {
+        self.0
+            .world
+            .get()
+            .unwrap_or_else(|| panic!("World {:?} is not loaded yet", self.0.name))
+    }
+}
+
+impl PartialEq for WorldRef {
+    fn eq(&self, other: &Self) -> bool {
+        std::sync::Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Debug for WorldRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WorldRef({:?})", self.name())
+    }
+}
+
+impl Serialize for WorldRef {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.name().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldRef {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::find(&name))
+    }
+}
+
+#[test]
+fn test_synthetic_world() {
+    let height = |value: u8| image::RgbaImage::from_pixel(8, 8, image::Rgba([value, 0, 0, 0xff]));
+    let world = |name: &str, value: u8| {
+        let manifest = MapManifest {
+            name: name.to_owned(),
+            heightmap: default(),
+            colormap: default(),
+            size: 10.0,
+            deepest_depth: -1.5,
+            highest_land: 0.5,
+            fish_list: default(),
+            fish_shops: vec![],
+            boat_shops: vec![vec![]; 3],
+        };
+        let map = Map::new(&manifest, height(value), height(value));
+        let fish_configs = FishConfigs {
+            configs: serde_json::from_str(
+                r#"[{
+                    "name": "Test fish",
+                    "cost": 1,
+                    "size": 1,
+                    "spawn_circle": { "center": [0, 0], "radius": 5, "behavior": "Chaos" },
+                    "count": 10
+                }]"#,
+            )
+            .unwrap(),
+        };
+        WorldRef::register(World::new(manifest, map, fish_configs).unwrap())
+    };
+    let mut sea = Model::new(world("test sea", 0));
+    let mut land = Model::new(world("test land", 0xff));
+    for _ in 0..10 {
+        <Model as simple_net::Model>::tick(&mut sea, &mut Vec::new());
+        <Model as simple_net::Model>::tick(&mut land, &mut Vec::new());
+    }
+    assert_eq!(sea.fishes.len(), 10);
+    assert!(sea
+        .fishes
+        .iter()
+        .all(|fish| sea.world.map.is_water(fish.pos.pos)));
+    assert!(!land.world.map.is_water(Vec2::ZERO));
+
+    let json = serde_json::to_string(&sea).unwrap();
+    let received: Model = serde_json::from_str(&json).unwrap();
+    assert!(received.world == sea.world);
+}
@@ -6,6 +6,7 @@ uniform sampler2D surfaceNoise;
 const vec3 surface_noise_scroll = vec3(0.00, 0.2, 0.0);
 
 varying vec2 v_uv;
+varying float v_depth;
 
 const float SMOOTHSTEP_AA = 0.01;
 
@@ -22,6 +23,7 @@ void main() {
     v_uv = a_uv;
     v_uv.y = a_v.z / (-5.0);
     v_uv.x = a_v.x / 200.0 + 0.5;
+    v_depth = length((u_view_matrix * vec4(a_v, 1.0)).xyz);
     gl_Position = u_projection_matrix * u_view_matrix * vec4(a_v, 1.0);
 }
 #endif
@@ -51,6 +53,10 @@ vec4 alphaBlend(vec4 top, vec4 bottom)
 
 #ifdef FRAGMENT_SHADER
 uniform float u_time;
+uniform vec4 u_light;
+uniform float u_fog_density;
+const vec3 FOG_COLOR = vec3(0.75, 0.78, 0.8);
+
 void main() {
     float x = sin((v_uv.y - u_time) * 5.0) * 0.5 + 0.5;
     x *= 0.2;
@@ -87,5 +93,7 @@ void main() {
 	surfaceNoiseSample = texture2D(surfaceNoise, noise_uv).r;
 	surfaceNoiseAmount = smoothstep(surfaceNoiseCutoff - SMOOTHSTEP_AA, surfaceNoiseCutoff + SMOOTHSTEP_AA, surfaceNoiseSample);
 	gl_FragColor = mix(gl_FragColor, vec4(1.0,0.0,0.0,0.0), surfaceNoiseAmount);
+	gl_FragColor.xyz *= u_light.xyz;
+    gl_FragColor.xyz = mix(u_light.xyz * FOG_COLOR, gl_FragColor.xyz, exp(-u_fog_density * v_depth));
 }
 #endif
\ No newline at end of file
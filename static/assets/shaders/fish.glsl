@@ -1,6 +1,7 @@
 varying vec2 v_uv;
 varying vec3 v_v;
 varying float v_height;
+varying float v_depth;
 varying mat4 v_model_matrix;
 // varying vec4 v_color;
 
@@ -21,17 +22,23 @@ void main() {
     // v_color = i_color;
     v_uv = a_uv;
     v_v = a_v;
+    v_depth = length((u_view_matrix * i_model_matrix * vec4(a_v, 1.0)).xyz);
     gl_Position = u_projection_matrix * u_view_matrix * i_model_matrix * vec4(a_v, 1.0);
 }
 #endif
 
 #ifdef FRAGMENT_SHADER
 uniform sampler2D u_texture;
+uniform vec4 u_light;
+uniform float u_fog_density;
+const vec3 FOG_COLOR = vec3(0.75, 0.78, 0.8);
 void main() {
     gl_FragColor = texture2D(u_texture, v_uv);
     if (gl_FragColor.w < 0.5) {
         discard;
     }
+    gl_FragColor.xyz *= u_light.xyz;
+    gl_FragColor.xyz = mix(u_light.xyz * FOG_COLOR, gl_FragColor.xyz, exp(-u_fog_density * v_depth));
     if (v_height < 0.0 && distance(vec3(0.0), v_model_matrix[3].xyz) < 100.0) {
         gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
     }
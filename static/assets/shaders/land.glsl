@@ -1,4 +1,5 @@
 varying vec2 v_uv;
+varying float v_depth;
 
 #ifdef VERTEX_SHADER
 attribute vec3 a_v;
@@ -9,14 +10,20 @@ uniform mat4 u_view_matrix;
 
 void main() {
     v_uv = a_uv;
+    v_depth = length((u_view_matrix * vec4(a_v, 1.0)).xyz);
     gl_Position = u_projection_matrix * u_view_matrix * vec4(a_v, 1.0);
 }
 #endif
 
 #ifdef FRAGMENT_SHADER
 uniform sampler2D u_texture;
+uniform vec4 u_light;
+uniform float u_fog_density;
+const vec3 FOG_COLOR = vec3(0.75, 0.78, 0.8);
 
 void main() {
     gl_FragColor = texture2D(u_texture, v_uv);
+    gl_FragColor.xyz *= u_light.xyz;
+    gl_FragColor.xyz = mix(u_light.xyz * FOG_COLOR, gl_FragColor.xyz, exp(-u_fog_density * v_depth));
 }
 #endif
\ No newline at end of file
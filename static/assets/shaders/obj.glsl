@@ -1,5 +1,6 @@
 varying vec2 v_uv;
 varying float v_light;
+varying float v_depth;
 
 #ifdef VERTEX_SHADER
 attribute vec3 a_v;
@@ -14,6 +15,7 @@ void main() {
     v_uv = a_uv;
     v_light = dot(mat3(i_model_matrix) * a_vn, normalize(vec3(1.0, 2.0, 3.0)));
     v_light = max(v_light, 0.0) * 0.2 + 0.8;
+    v_depth = length((u_view_matrix * i_model_matrix * vec4(a_v, 1.0)).xyz);
     gl_Position = u_projection_matrix * u_view_matrix * i_model_matrix * vec4(a_v, 1.0);
 }
 #endif
@@ -21,9 +23,13 @@ void main() {
 #ifdef FRAGMENT_SHADER
 uniform sampler2D u_texture;
 uniform vec4 u_color;
+uniform vec4 u_light;
+uniform float u_fog_density;
+const vec3 FOG_COLOR = vec3(0.75, 0.78, 0.8);
 void main() {
     gl_FragColor = texture2D(u_texture, v_uv) * u_color;
-    gl_FragColor.xyz *= v_light;
+    gl_FragColor.xyz *= v_light * u_light.xyz;
+    gl_FragColor.xyz = mix(u_light.xyz * FOG_COLOR, gl_FragColor.xyz, exp(-u_fog_density * v_depth));
     if (gl_FragColor.w < 0.5) {
         discard;
     }
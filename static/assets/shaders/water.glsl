@@ -67,6 +67,9 @@ void main() {
 uniform vec2 u_framebuffer_size;
 uniform sampler2D u_depth_texture;
 uniform sampler2D u_heightmap;
+uniform vec4 u_light;
+uniform float u_fog_density;
+const vec3 FOG_COLOR = vec3(0.75, 0.78, 0.8);
 void main(){
 	// https://www.youtube.com/watch?v=Jq3he9Lbj7M
 	float depth = unpack4(texture2D(u_depth_texture, gl_FragCoord.xy / u_framebuffer_size));
@@ -104,6 +107,8 @@ void main(){
 	vec4 color = alphaBlend(surfaceNoiseColor, waterColor);
 	
     gl_FragColor = color;
+    gl_FragColor.xyz *= u_light.xyz;
+    gl_FragColor.xyz = mix(u_light.xyz * FOG_COLOR, gl_FragColor.xyz, exp(-u_fog_density * length(v_eye_pos.xyz)));
 
 	// ALBEDO = color.rgb;
 	// ALPHA = color.a;